@@ -0,0 +1,347 @@
+use soroban_sdk::contracttype;
+
+use crate::{LifeStage, MAX_STAT};
+
+const HOUR: u64 = 3600;
+const DAY: u64 = 24 * HOUR;
+
+/// Game balance parameters. Stored on-chain so the admin can rebalance the
+/// live game with `set_config` instead of redeploying.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameConfig {
+    // Upper bound for every stat, new pets start here
+    pub max_stat: u32,
+
+    // Decay: stats drop once every `decay_period` seconds at the rates of the
+    // pet's life stage, hunger only every `sleep_hunger_decay_periods`
+    // periods while asleep
+    pub decay_period: u64,
+    pub sleep_hunger_decay_periods: u64,
+
+    // Aging: how long each life stage lasts and how the pet lives through it
+    pub stages: LifeStages,
+    // How the care received during a stage shapes the next form
+    pub evolution: EvolutionRules,
+
+    // Energy: an awake pet tires by 1 every `energy_drain_periods` periods,
+    // a sleeping one recovers `energy_recovery` every period. Periods slept
+    // through in part count for the share of them spent asleep.
+    pub energy_drain_periods: u64,
+    pub energy_recovery: u32,
+
+    // Hygiene: cleanliness drops by 1 every `cleanliness_decay_periods`
+    // periods, and by `digestion_cleanliness_decay` every period during the
+    // `digestion_time` seconds after a meal. A pet at or below
+    // `dirty_threshold` loses an extra point of happiness every period.
+    pub cleanliness_decay_periods: u64,
+    pub digestion_time: u64,
+    pub digestion_cleanliness_decay: u32,
+    pub dirty_threshold: u32,
+
+    // Action effects
+    pub feed_hunger: u32,
+    pub play_happiness: u32,
+    pub play_energy_cost: u32,
+    pub work_energy_cost: u32,
+    pub work_happiness_cost: u32,
+
+    // Over-saturation: feeding a pet whose hunger is above `overfeed_threshold`
+    // costs `overfeed_happiness_penalty` happiness, and the nth play in a row
+    // (each within `play_streak_window` seconds of the last) gives 1/n of
+    // `play_happiness`
+    pub overfeed_threshold: u32,
+    pub overfeed_happiness_penalty: u32,
+    pub play_streak_window: u64,
+
+    // Health: a pet whose hunger, happiness or cleanliness is at or below
+    // `sickness_threshold`, or that is overfed `overfeed_sickness_count` times
+    // in a row, falls sick. Sickness costs `sick_health_drain` health every
+    // period and each empty stat `neglect_health_drain` more, otherwise health
+    // recovers by `health_recovery`. Medicine cures and restores
+    // `medicine_health`.
+    pub sickness_threshold: u32,
+    pub overfeed_sickness_count: u32,
+    pub sick_health_drain: u32,
+    pub neglect_health_drain: u32,
+    pub health_recovery: u32,
+    pub medicine_health: u32,
+
+    // Revival: a pet that died of anything but old age can be brought back
+    // within `revive_window` seconds of its death, with every stat at
+    // `revive_stat`
+    pub revive_window: u64,
+    pub revive_stat: u32,
+
+    // Cooldowns: seconds before the same action can be repeated, 0 disables
+    pub feed_cooldown: u64,
+    pub play_cooldown: u64,
+    pub work_cooldown: u64,
+
+    // Economy
+    pub work_reward: i128,
+    pub medicine_price: i128,
+    pub revive_price: i128,
+
+    // Pets an owner may hold at once, dead ones included until removed
+    pub max_pets_per_owner: u32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            max_stat: MAX_STAT,
+            decay_period: HOUR,
+            sleep_hunger_decay_periods: 2,
+            stages: LifeStages::default(),
+            evolution: EvolutionRules::default(),
+            energy_drain_periods: 3,
+            energy_recovery: 10,
+            cleanliness_decay_periods: 2,
+            digestion_time: 2 * 3600,
+            digestion_cleanliness_decay: 5,
+            dirty_threshold: 30,
+            feed_hunger: 30,
+            play_happiness: 20,
+            play_energy_cost: 15,
+            work_energy_cost: 20,
+            work_happiness_cost: 10,
+            overfeed_threshold: 90,
+            overfeed_happiness_penalty: 10,
+            play_streak_window: 4 * 3600,
+            sickness_threshold: 20,
+            overfeed_sickness_count: 3,
+            sick_health_drain: 2,
+            neglect_health_drain: 10,
+            health_recovery: 1,
+            medicine_health: 20,
+            revive_window: DAY,
+            revive_stat: 50,
+            feed_cooldown: 1800,
+            play_cooldown: 1800,
+            work_cooldown: 3600,
+            work_reward: 25,
+            medicine_price: 30,
+            revive_price: 100,
+            max_pets_per_owner: 10,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn is_valid(&self) -> bool {
+        self.max_stat > 0
+            && self.decay_period > 0
+            && self.sleep_hunger_decay_periods > 0
+            && self.stages.is_valid()
+            && self.evolution.is_valid()
+            && self.energy_drain_periods > 0
+            && self.cleanliness_decay_periods > 0
+            && self.work_reward >= 0
+            && self.medicine_price >= 0
+            && self.revive_price >= 0
+            // A revived pet has to be alive, with no stat past the maximum
+            && (1..=self.max_stat).contains(&self.revive_stat)
+            && self.max_pets_per_owner > 0
+    }
+}
+
+/// Rules for one stage of a pet's life after hatching.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StageRules {
+    // Age in seconds at which the pet grows out of this stage
+    pub ends_at: u64,
+    // Hunger lost every period, and periods between happiness losses
+    pub hunger_decay: u32,
+    pub happiness_decay_periods: u64,
+    pub can_work: bool,
+}
+
+/// Life stages a pet goes through, in order. An egg neither decays nor
+/// accepts any action until `hatch_time` seconds after its birth, and an
+/// elder dies of old age once it outlives `elder.ends_at`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LifeStages {
+    pub hatch_time: u64,
+    pub baby: StageRules,
+    pub child: StageRules,
+    pub teen: StageRules,
+    pub adult: StageRules,
+    pub elder: StageRules,
+}
+
+impl Default for LifeStages {
+    fn default() -> Self {
+        LifeStages {
+            hatch_time: HOUR,
+            // Babies get hungry and bored twice as fast and are too young to work
+            baby: StageRules {
+                ends_at: DAY,
+                hunger_decay: 2,
+                happiness_decay_periods: 1,
+                can_work: false,
+            },
+            child: StageRules {
+                ends_at: 3 * DAY,
+                hunger_decay: 1,
+                happiness_decay_periods: 2,
+                can_work: true,
+            },
+            teen: StageRules {
+                ends_at: 7 * DAY,
+                hunger_decay: 1,
+                happiness_decay_periods: 2,
+                can_work: true,
+            },
+            adult: StageRules {
+                ends_at: 60 * DAY,
+                hunger_decay: 1,
+                happiness_decay_periods: 2,
+                can_work: true,
+            },
+            // Elders get lonely quickly
+            elder: StageRules {
+                ends_at: 90 * DAY,
+                hunger_decay: 1,
+                happiness_decay_periods: 1,
+                can_work: true,
+            },
+        }
+    }
+}
+
+impl LifeStages {
+    /// The stage of a pet that is `age` seconds old. Pets past their natural
+    /// lifespan stay elders.
+    pub fn stage_at(&self, age: u64) -> LifeStage {
+        if age < self.hatch_time {
+            LifeStage::Egg
+        } else if age < self.baby.ends_at {
+            LifeStage::Baby
+        } else if age < self.child.ends_at {
+            LifeStage::Child
+        } else if age < self.teen.ends_at {
+            LifeStage::Teen
+        } else if age < self.adult.ends_at {
+            LifeStage::Adult
+        } else {
+            LifeStage::Elder
+        }
+    }
+
+    /// Rules for `stage`, eggs have none.
+    pub fn rules(&self, stage: LifeStage) -> Option<&StageRules> {
+        match stage {
+            LifeStage::Egg => None,
+            LifeStage::Baby => Some(&self.baby),
+            LifeStage::Child => Some(&self.child),
+            LifeStage::Teen => Some(&self.teen),
+            LifeStage::Adult => Some(&self.adult),
+            LifeStage::Elder => Some(&self.elder),
+        }
+    }
+
+    /// Age in seconds at which a pet enters `stage`.
+    pub fn starts_at(&self, stage: LifeStage) -> u64 {
+        match stage {
+            LifeStage::Egg => 0,
+            LifeStage::Baby => self.hatch_time,
+            LifeStage::Child => self.baby.ends_at,
+            LifeStage::Teen => self.child.ends_at,
+            LifeStage::Adult => self.teen.ends_at,
+            LifeStage::Elder => self.adult.ends_at,
+        }
+    }
+
+    /// Age in seconds at which a pet dies of old age.
+    pub fn lifespan(&self) -> u64 {
+        self.elder.ends_at
+    }
+
+    fn is_valid(&self) -> bool {
+        let stages = [
+            &self.baby,
+            &self.child,
+            &self.teen,
+            &self.adult,
+            &self.elder,
+        ];
+        let mut start = self.hatch_time;
+        for rules in stages {
+            if rules.ends_at < start || rules.happiness_decay_periods == 0 {
+                return false;
+            }
+            start = rules.ends_at;
+        }
+        // A pet has to live past hatching
+        self.lifespan() > self.hatch_time
+    }
+}
+
+/// Thresholds grading the care a pet received during a stage when it grows
+/// into the next one, see `engine::care_grade`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EvolutionRules {
+    // A meal counts as missed every time hunger drops to this level
+    pub hungry_threshold: u32,
+    // Great care: at most `great_missed_feedings` missed meals and an average
+    // happiness of at least `great_happiness`
+    pub great_missed_feedings: u32,
+    pub great_happiness: u32,
+    // Poor care: `poor_missed_feedings` missed meals or more, or an average
+    // happiness below `poor_happiness`
+    pub poor_missed_feedings: u32,
+    pub poor_happiness: u32,
+    // Pets that worked this many times during a stage grow into workers
+    pub hard_work_count: u32,
+}
+
+impl Default for EvolutionRules {
+    fn default() -> Self {
+        EvolutionRules {
+            hungry_threshold: 40,
+            great_missed_feedings: 0,
+            great_happiness: 70,
+            poor_missed_feedings: 3,
+            poor_happiness: 40,
+            hard_work_count: 10,
+        }
+    }
+}
+
+impl EvolutionRules {
+    fn is_valid(&self) -> bool {
+        self.great_missed_feedings < self.poor_missed_feedings
+            && self.great_happiness >= self.poor_happiness
+    }
+}
+
+#[cfg(test)]
+impl GameConfig {
+    /// Default rules for a pet that hatches as an adult and never grows old,
+    /// so tests can exercise the other rules without aging in the way.
+    pub fn ageless() -> Self {
+        let adult = StageRules {
+            ends_at: u64::MAX,
+            ..LifeStages::default().adult
+        };
+        let skipped = StageRules {
+            ends_at: 0,
+            ..adult.clone()
+        };
+        GameConfig {
+            stages: LifeStages {
+                hatch_time: 0,
+                baby: skipped.clone(),
+                child: skipped.clone(),
+                teen: skipped,
+                adult: adult.clone(),
+                elder: adult,
+            },
+            ..GameConfig::default()
+        }
+    }
+}
@@ -0,0 +1,566 @@
+//! Pure game rules.
+//!
+//! Everything in this module works on plain values: no `Env`, no storage and
+//! no host objects. The contract loads a pet, converts it to a [`PetState`],
+//! runs it through these functions and writes the result back, while balance
+//! simulations can call the very same functions directly for millions of ticks.
+use crate::{
+    CareMetrics, DeathCause, Error, Evolution, GameConfig, LifeStage, PetForm, StageRules,
+    StatEffects,
+};
+
+/// The part of a pet the rules operate on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PetState {
+    pub born_at: u64,
+    pub stage: LifeStage,
+    pub form: PetForm,
+    pub care: CareMetrics,
+    pub last_updated: u64,
+    pub is_alive: bool,
+    pub cause_of_death: Option<DeathCause>,
+    pub died_at: Option<u64>,
+    pub hunger: u32,
+    pub happiness: u32,
+    pub energy: u32,
+    pub health: u32,
+    pub cleanliness: u32,
+    pub is_sick: bool,
+    pub overfeed_count: u32,
+    pub asleep_since: Option<u64>,
+    // Seconds slept since `last_updated` in naps that are already over
+    pub napped: u64,
+    pub last_fed: Option<u64>,
+    pub last_played: Option<u64>,
+    pub last_worked: Option<u64>,
+    pub play_streak: u32,
+}
+
+impl PetState {
+    /// A newborn pet with every stat at the maximum.
+    pub fn new(config: &GameConfig, now: u64) -> Self {
+        let stage = config.stages.stage_at(0);
+        PetState {
+            born_at: now,
+            stage,
+            form: evolved_form(stage, CareGrade::Fair, false),
+            care: CareMetrics::default(),
+            last_updated: now,
+            is_alive: true,
+            cause_of_death: None,
+            died_at: None,
+            hunger: config.max_stat,
+            happiness: config.max_stat,
+            energy: config.max_stat,
+            health: config.max_stat,
+            cleanliness: config.max_stat,
+            is_sick: false,
+            overfeed_count: 0,
+            asleep_since: None,
+            napped: 0,
+            last_fed: None,
+            last_played: None,
+            last_worked: None,
+            play_streak: 0,
+        }
+    }
+}
+
+/// Care actions a player can perform on a living pet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    Feed,
+    Play,
+    Sleep,
+    Wake,
+    Work,
+    Medicine,
+    Clean,
+}
+
+// Upper bound on the periods simulated by a single decay. Far beyond the
+// unattended lifespan of a pet under any sane config, it only keeps the cost
+// bounded for degenerate ones.
+const MAX_DECAY_PERIODS: u64 = 10_000;
+
+/// The life stage of a pet at `now`.
+pub fn life_stage(config: &GameConfig, state: &PetState, now: u64) -> LifeStage {
+    config.stages.stage_at(now.saturating_sub(state.born_at))
+}
+
+/// How well a pet was looked after during a stage.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CareGrade {
+    Great,
+    Fair,
+    Poor,
+}
+
+/// Grade the care a pet received since it entered its current stage.
+pub fn care_grade(config: &GameConfig, state: &PetState) -> CareGrade {
+    let rules = &config.evolution;
+    let missed = state.care.missed_feedings;
+    let happiness = average_happiness(state);
+    if missed >= rules.poor_missed_feedings || happiness < rules.poor_happiness {
+        CareGrade::Poor
+    } else if missed <= rules.great_missed_feedings && happiness >= rules.great_happiness {
+        CareGrade::Great
+    } else {
+        CareGrade::Fair
+    }
+}
+
+/// The evolution table: the form a pet takes when it enters `stage` after
+/// receiving `grade` care. Hard workers that were not neglected grow into
+/// workhorses.
+pub fn evolved_form(stage: LifeStage, grade: CareGrade, hard_worker: bool) -> PetForm {
+    match (stage, grade) {
+        (LifeStage::Egg, _) => PetForm::Egg,
+        (LifeStage::Baby, _) => PetForm::Blob,
+        (LifeStage::Child, CareGrade::Great) => PetForm::Sprout,
+        (LifeStage::Child, CareGrade::Fair) => PetForm::Pup,
+        (LifeStage::Child, CareGrade::Poor) => PetForm::Grub,
+        (LifeStage::Teen, CareGrade::Great) => PetForm::Sparkle,
+        (LifeStage::Teen, CareGrade::Fair) => PetForm::Scamp,
+        (LifeStage::Teen, CareGrade::Poor) => PetForm::Grump,
+        (LifeStage::Adult, CareGrade::Poor) => PetForm::Gremlin,
+        (LifeStage::Adult, _) if hard_worker => PetForm::Workhorse,
+        (LifeStage::Adult, CareGrade::Great) => PetForm::Guardian,
+        (LifeStage::Adult, CareGrade::Fair) => PetForm::Buddy,
+        (LifeStage::Elder, CareGrade::Great) => PetForm::Sage,
+        (LifeStage::Elder, CareGrade::Fair) => PetForm::Veteran,
+        (LifeStage::Elder, CareGrade::Poor) => PetForm::Grouch,
+    }
+}
+
+// Average happiness over the current stage, the current happiness when the
+// pet has not lived a full period in it yet
+fn average_happiness(state: &PetState) -> u32 {
+    state.care.average_happiness().unwrap_or(state.happiness)
+}
+
+/// Apply the stat decay accumulated since `last_updated`.
+/// Returns the cause of death if the pet died during this decay, the moment
+/// it died is kept in `died_at` however late the decay is applied.
+pub fn decay(config: &GameConfig, state: &mut PetState, now: u64) -> Option<DeathCause> {
+    decay_with(config, state, now, |_| {})
+}
+
+/// Same as [`decay`], reporting every stage the pet grows into along the way
+/// to `on_evolve`, in order.
+pub fn decay_with(
+    config: &GameConfig,
+    state: &mut PetState,
+    now: u64,
+    mut on_evolve: impl FnMut(Evolution),
+) -> Option<DeathCause> {
+    if !state.is_alive {
+        return None;
+    }
+
+    let time_elapsed = now.saturating_sub(state.last_updated);
+
+    // Stats decay once per period (an hour by default)
+    let decay_periods = time_elapsed / config.decay_period;
+    if decay_periods == 0 {
+        grow(
+            config,
+            state,
+            life_stage(config, state, state.last_updated),
+            &mut on_evolve,
+        );
+        return None;
+    }
+    // Only whole periods are consumed, the seconds left over count towards
+    // the next one so decay doesn't depend on how often the pet is looked at
+    let decay_periods = decay_periods.min(MAX_DECAY_PERIODS);
+    let start = state.last_updated;
+    state.last_updated = start.saturating_add(decay_periods.saturating_mul(config.decay_period));
+
+    // Walk through the gap one period at a time so sickness and health react
+    // to the stats as they were back then, and each period decays at the
+    // rates of the stage the pet was in when it started
+    for period in 1..=decay_periods {
+        let period_start = start.saturating_add((period - 1).saturating_mul(config.decay_period));
+        let period_end = period_start.saturating_add(config.decay_period);
+        let stage = life_stage(config, state, period_start);
+        grow(config, state, stage, &mut on_evolve);
+        // Naps that are over all fall within the first period, the one that
+        // was in progress when they ended
+        let napped = core::mem::take(&mut state.napped);
+        // Eggs don't need any care yet
+        let Some(rules) = config.stages.rules(stage) else {
+            continue;
+        };
+        let asleep_now = state.asleep_since.map_or(0, |since| {
+            period_end.saturating_sub(since.max(period_start))
+        });
+        let slept = napped.saturating_add(asleep_now).min(config.decay_period);
+        decay_one_period(config, rules, state, period_end, slept);
+
+        // Health drops at the end of the period, old age strikes on the
+        // pet's last birthday even in the middle of one
+        let end_of_life = state.born_at.saturating_add(config.stages.lifespan());
+        let (cause, died_at) = if state.health == 0 {
+            let cause = if state.hunger == 0 {
+                DeathCause::Starvation
+            } else if state.happiness == 0 {
+                DeathCause::Sadness
+            } else {
+                DeathCause::Sickness
+            };
+            (cause, period_end)
+        } else if period_end >= end_of_life {
+            (DeathCause::OldAge, end_of_life)
+        } else {
+            continue;
+        };
+        // Stats stay frozen as they were when the pet died
+        state.last_updated = period_end;
+        state.is_alive = false;
+        state.cause_of_death = Some(cause);
+        state.died_at = Some(died_at);
+        return Some(cause);
+    }
+    // Pets only grow at the start of a period, a stage that begins in the
+    // middle of one starts with the next so the period in progress decays
+    // and is graded with the stage it began in however often it is read
+    grow(
+        config,
+        state,
+        life_stage(config, state, state.last_updated),
+        &mut on_evolve,
+    );
+    None
+}
+
+// Move the pet on to `stage`, evolving once for every stage it enters and
+// starting a fresh care record each time. Pets never grow younger.
+fn grow(
+    config: &GameConfig,
+    state: &mut PetState,
+    stage: LifeStage,
+    on_evolve: &mut impl FnMut(Evolution),
+) {
+    while state.stage < stage {
+        let next = next_stage(state.stage);
+        let grade = care_grade(config, state);
+        let hard_worker = state.care.times_worked >= config.evolution.hard_work_count;
+
+        state.stage = next;
+        state.form = evolved_form(next, grade, hard_worker);
+        on_evolve(Evolution {
+            stage: next,
+            form: state.form,
+            evolved_at: state.born_at.saturating_add(config.stages.starts_at(next)),
+            missed_feedings: state.care.missed_feedings,
+            average_happiness: average_happiness(state),
+            times_worked: state.care.times_worked,
+        });
+        state.care = CareMetrics::default();
+    }
+}
+
+fn next_stage(stage: LifeStage) -> LifeStage {
+    match stage {
+        LifeStage::Egg => LifeStage::Baby,
+        LifeStage::Baby => LifeStage::Child,
+        LifeStage::Child => LifeStage::Teen,
+        LifeStage::Teen => LifeStage::Adult,
+        LifeStage::Adult | LifeStage::Elder => LifeStage::Elder,
+    }
+}
+
+// `period_end` is the timestamp the period ends at and `slept` the seconds of
+// it the pet spent asleep. Stats that change less often than once per period
+// do so when the number of periods the pet has lived is a multiple of their
+// interval.
+fn decay_one_period(
+    config: &GameConfig,
+    rules: &StageRules,
+    state: &mut PetState,
+    period_end: u64,
+    slept: u64,
+) {
+    let period = period_end.saturating_sub(state.born_at) / config.decay_period;
+    let awake = config.decay_period - slept;
+    let hunger_before = state.hunger;
+
+    // Hunger decays by 1 point per hour for a grown pet, sleeping pets get
+    // hungry at half that pace
+    let hungry_for = if period.is_multiple_of(config.sleep_hunger_decay_periods) {
+        config.decay_period
+    } else {
+        awake
+    };
+    let hunger_loss = share(config, rules.hunger_decay, hungry_for);
+    state.hunger = state.hunger.saturating_sub(hunger_loss);
+    // Happiness decays by 1 point per 2 hours for a grown pet
+    if period.is_multiple_of(rules.happiness_decay_periods) {
+        state.happiness = state.happiness.saturating_sub(1);
+    }
+    // Energy comes back for the time spent asleep and slowly drains for the
+    // time spent awake, so a short nap is worth a little and no more
+    let recovered = share(config, config.energy_recovery, slept);
+    state.energy = add_capped(state.energy, recovered, config.max_stat);
+    if period.is_multiple_of(config.energy_drain_periods) {
+        state.energy = state.energy.saturating_sub(share(config, 1, awake));
+    }
+    // Pets get messy over time, and much faster while digesting a meal
+    let digesting = state
+        .last_fed
+        .is_some_and(|fed| period_end <= fed.saturating_add(config.digestion_time));
+    if digesting {
+        state.cleanliness = state
+            .cleanliness
+            .saturating_sub(config.digestion_cleanliness_decay);
+    } else if period.is_multiple_of(config.cleanliness_decay_periods) {
+        state.cleanliness = state.cleanliness.saturating_sub(1);
+    }
+    // Nobody enjoys sitting in a mess
+    if state.cleanliness <= config.dirty_threshold {
+        state.happiness = state.happiness.saturating_sub(1);
+    }
+
+    // Going hungry, unhappy or dirty for too long makes the pet sick
+    if state.hunger <= config.sickness_threshold
+        || state.happiness <= config.sickness_threshold
+        || state.cleanliness <= config.sickness_threshold
+    {
+        state.is_sick = true;
+    }
+
+    let mut health_loss: u32 = 0;
+    if state.is_sick {
+        health_loss = health_loss.saturating_add(config.sick_health_drain);
+    }
+    if state.hunger == 0 {
+        health_loss = health_loss.saturating_add(config.neglect_health_drain);
+    }
+    if state.happiness == 0 {
+        health_loss = health_loss.saturating_add(config.neglect_health_drain);
+    }
+    state.health = if health_loss == 0 {
+        add_capped(state.health, config.health_recovery, config.max_stat)
+    } else {
+        state.health.saturating_sub(health_loss)
+    };
+
+    // Keep score of the care received for the next evolution
+    let hungry = config.evolution.hungry_threshold;
+    if hunger_before > hungry && state.hunger <= hungry {
+        state.care.missed_feedings = state.care.missed_feedings.saturating_add(1);
+    }
+    state.care.happiness_total = state
+        .care
+        .happiness_total
+        .saturating_add(u64::from(state.happiness));
+    state.care.periods = state.care.periods.saturating_add(1);
+}
+
+/// Apply an action to an already decayed pet. Actions leave `last_updated`
+/// alone, the period in progress still decays in full once it is over.
+/// A sleeping pet only accepts `Wake`; energy comes back with time, not from `Sleep`.
+/// Eggs accept nothing, and only stages that allow it can `Work`.
+pub fn perform(
+    config: &GameConfig,
+    state: &mut PetState,
+    action: Action,
+    now: u64,
+) -> Result<(), Error> {
+    perform_with(config, state, action, now, None)
+}
+
+/// Same as [`perform`], with an item used for the action. Its effects replace
+/// the usual gain of `Feed`, `Play` and `Medicine`: the hunger of a meal, the
+/// happiness and energy of a game, the health of a cure. Everything else about
+/// the action, from cooldowns to overfeeding, works the same. Items leave the
+/// pet at least 1 health however harmful they are.
+pub fn perform_with(
+    config: &GameConfig,
+    state: &mut PetState,
+    action: Action,
+    now: u64,
+    item: Option<StatEffects>,
+) -> Result<(), Error> {
+    if !state.is_alive {
+        return Err(Error::PetDead);
+    }
+    let allowed = match config.stages.rules(life_stage(config, state, now)) {
+        Some(rules) => action != Action::Work || rules.can_work,
+        None => false,
+    };
+    if !allowed {
+        return Err(Error::NotAllowedAtStage);
+    }
+
+    let asleep = state.asleep_since.is_some();
+    match action {
+        Action::Sleep => {
+            if asleep {
+                return Err(Error::AlreadyAsleep);
+            }
+            state.asleep_since = Some(now);
+        }
+        Action::Wake => {
+            if !asleep {
+                return Err(Error::NotAsleep);
+            }
+            // Count the nap towards the period in progress, the periods
+            // before it were already decayed with the pet asleep
+            let since = state.asleep_since.unwrap_or(now).max(state.last_updated);
+            state.napped = state.napped.saturating_add(now.saturating_sub(since));
+            state.asleep_since = None;
+        }
+        _ if asleep => return Err(Error::PetAsleep),
+        _ if cooldown_remaining(config, state, action, now) > 0 => {
+            return Err(Error::ActionOnCooldown)
+        }
+        Action::Feed => {
+            // Stuffing a pet that isn't hungry makes it grumpy, and sick
+            // when it keeps happening
+            if state.hunger > config.overfeed_threshold {
+                state.happiness = state
+                    .happiness
+                    .saturating_sub(config.overfeed_happiness_penalty);
+                state.overfeed_count = state.overfeed_count.saturating_add(1);
+                if state.overfeed_count >= config.overfeed_sickness_count {
+                    state.is_sick = true;
+                }
+            } else {
+                state.overfeed_count = 0;
+            }
+            match item {
+                Some(effects) => apply_effects(config, state, effects),
+                None => {
+                    state.hunger = add_capped(state.hunger, config.feed_hunger, config.max_stat)
+                }
+            }
+            state.last_fed = Some(now);
+        }
+        Action::Play => {
+            state.play_streak = match state.last_played {
+                Some(last) if now.saturating_sub(last) < config.play_streak_window => {
+                    state.play_streak.saturating_add(1)
+                }
+                _ => 0,
+            };
+            match item {
+                Some(effects) => apply_effects(config, state, effects),
+                None => {
+                    let gain = play_gain(config, state.play_streak);
+                    state.happiness = add_capped(state.happiness, gain, config.max_stat);
+                    state.energy = state.energy.saturating_sub(config.play_energy_cost);
+                }
+            }
+            state.last_played = Some(now);
+        }
+        Action::Work => {
+            if state.energy < config.work_energy_cost {
+                return Err(Error::InsufficientEnergy);
+            }
+            state.energy -= config.work_energy_cost;
+            state.happiness = state.happiness.saturating_sub(config.work_happiness_cost);
+            state.last_worked = Some(now);
+            state.care.times_worked = state.care.times_worked.saturating_add(1);
+        }
+        Action::Medicine => {
+            if !state.is_sick {
+                return Err(Error::NotSick);
+            }
+            state.is_sick = false;
+            state.overfeed_count = 0;
+            match item {
+                Some(effects) => apply_effects(config, state, effects),
+                None => {
+                    state.health = add_capped(state.health, config.medicine_health, config.max_stat)
+                }
+            }
+        }
+        Action::Clean => {
+            state.cleanliness = config.max_stat;
+        }
+    }
+
+    Ok(())
+}
+
+/// Bring a dead pet back to life within the revival window, every stat at
+/// `revive_stat` and nothing remembered of its illness or its nap.
+pub fn revive(config: &GameConfig, state: &mut PetState, now: u64) -> Result<(), Error> {
+    let (Some(cause), Some(died_at)) = (state.cause_of_death, state.died_at) else {
+        return Err(Error::PetNotDead);
+    };
+    if cause == DeathCause::OldAge {
+        return Err(Error::NotRevivable);
+    }
+    if now > died_at.saturating_add(config.revive_window) {
+        return Err(Error::RevivalWindowExpired);
+    }
+
+    let stat = config.revive_stat;
+    state.is_alive = true;
+    state.cause_of_death = None;
+    state.died_at = None;
+    state.hunger = stat;
+    state.happiness = stat;
+    state.energy = stat;
+    state.health = stat;
+    state.cleanliness = stat;
+    state.is_sick = false;
+    state.overfeed_count = 0;
+    state.asleep_since = None;
+    state.napped = 0;
+    // Time spent dead doesn't decay, the clock restarts at the beginning of
+    // the current period
+    let since_birth = now.saturating_sub(state.born_at);
+    state.last_updated = now - since_birth % config.decay_period;
+    Ok(())
+}
+
+/// Seconds left before `action` can be performed again, 0 when it is ready.
+/// Sleeping, waking, medicine and cleaning never cool down.
+pub fn cooldown_remaining(config: &GameConfig, state: &PetState, action: Action, now: u64) -> u64 {
+    let (last, cooldown) = match action {
+        Action::Feed => (state.last_fed, config.feed_cooldown),
+        Action::Play => (state.last_played, config.play_cooldown),
+        Action::Work => (state.last_worked, config.work_cooldown),
+        Action::Sleep | Action::Wake | Action::Medicine | Action::Clean => return 0,
+    };
+    match last {
+        Some(last) => last.saturating_add(cooldown).saturating_sub(now),
+        None => 0,
+    }
+}
+
+/// Happiness gained from a play, shrinking with every back-to-back play:
+/// full value for the first, half for the second, a third for the next...
+pub fn play_gain(config: &GameConfig, streak: u32) -> u32 {
+    config.play_happiness / streak.saturating_add(1)
+}
+
+// `amount` scaled to the part of a period `seconds` make up, rounded to the
+// nearest point
+fn share(config: &GameConfig, amount: u32, seconds: u64) -> u32 {
+    let scaled = u128::from(amount) * u128::from(seconds) + u128::from(config.decay_period / 2);
+    (scaled / u128::from(config.decay_period)) as u32
+}
+
+fn add_capped(stat: u32, amount: u32, max: u32) -> u32 {
+    stat.saturating_add(amount).min(max)
+}
+
+fn apply_effects(config: &GameConfig, state: &mut PetState, effects: StatEffects) {
+    let apply = |stat: u32, delta: i32| {
+        (i64::from(stat) + i64::from(delta)).clamp(0, i64::from(config.max_stat)) as u32
+    };
+    state.hunger = apply(state.hunger, effects.hunger);
+    state.happiness = apply(state.happiness, effects.happiness);
+    state.energy = apply(state.energy, effects.energy);
+    // Items can hurt a pet but never kill it, death only comes with decay
+    state.health = apply(state.health, effects.health).max(1);
+    state.cleanliness = apply(state.cleanliness, effects.cleanliness);
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,1245 @@
+extern crate std;
+
+use super::{
+    care_grade, cooldown_remaining, decay, decay_with, evolved_form, life_stage, perform,
+    perform_with, revive, Action, CareGrade, PetState,
+};
+use std::vec::Vec;
+
+use crate::{
+    CareMetrics, DeathCause, Error, Evolution, GameConfig, LifeStage, PetForm, StatEffects,
+};
+
+const HOUR: u64 = 3600;
+const DAY: u64 = 24 * HOUR;
+
+// Tiny xorshift generator so the property tests stay dependency free and
+// reproducible from their seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn action(&mut self) -> Action {
+        match self.below(5) {
+            0 => Action::Feed,
+            1 => Action::Play,
+            2 => Action::Sleep,
+            3 => Action::Wake,
+            _ => Action::Work,
+        }
+    }
+}
+
+fn pet_with(hunger: u32, happiness: u32, energy: u32) -> PetState {
+    PetState {
+        born_at: 0,
+        stage: LifeStage::Adult,
+        form: PetForm::Buddy,
+        care: CareMetrics::default(),
+        last_updated: 0,
+        is_alive: true,
+        cause_of_death: None,
+        died_at: None,
+        hunger,
+        happiness,
+        energy,
+        health: 100,
+        cleanliness: 100,
+        is_sick: false,
+        overfeed_count: 0,
+        asleep_since: None,
+        napped: 0,
+        last_fed: None,
+        last_played: None,
+        last_worked: None,
+        play_streak: 0,
+    }
+}
+
+#[test]
+fn test_new_pet_starts_full() {
+    let config = GameConfig::ageless();
+    let state = PetState::new(&config, 42);
+
+    let expected = pet_with(100, 100, 100).born(42).with_last_updated(42);
+    assert_eq!(state, expected);
+}
+
+#[test]
+fn test_decay_within_a_period_changes_nothing() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 100, 100);
+
+    assert_eq!(decay(&config, &mut state, HOUR - 1), None);
+    assert_eq!(state, pet_with(100, 100, 100));
+}
+
+#[test]
+fn test_decay_hunger_and_happiness() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 100, 100);
+
+    assert_eq!(decay(&config, &mut state, 27 * HOUR), None);
+    assert_eq!(state.hunger, 73);
+    assert_eq!(state.happiness, 87);
+    assert_eq!(state.energy, 91);
+    assert_eq!(state.cleanliness, 87);
+    assert_eq!(state.last_updated, 27 * HOUR);
+}
+
+#[test]
+fn test_energy_recovers_while_asleep() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 100, 10).asleep(0);
+
+    assert_eq!(decay(&config, &mut state, 5 * HOUR), None);
+    assert_eq!(state.energy, 60);
+    // Hunger only drops every other hour during the nap
+    assert_eq!(state.hunger, 98);
+
+    // Recovery stops at the maximum however long the nap lasts
+    assert_eq!(decay(&config, &mut state, 20 * HOUR), None);
+    assert_eq!(state.energy, 100);
+    assert_eq!(state.hunger, 90);
+    assert_eq!(state.asleep_since, Some(0));
+}
+
+#[test]
+fn test_sleep_counts_the_time_spent_asleep() {
+    let config = GameConfig::ageless();
+    let nap = |sleep: u64, wake: u64| {
+        let mut state = pet_with(100, 100, 10);
+        perform(&config, &mut state, Action::Sleep, sleep).unwrap();
+        decay(&config, &mut state, wake);
+        perform(&config, &mut state, Action::Wake, wake).unwrap();
+        decay(&config, &mut state, 2 * HOUR);
+        (state.energy, state.hunger)
+    };
+
+    // A second of sleep across a period boundary is worth nothing
+    assert_eq!(nap(HOUR - 1, HOUR), (10, 98));
+    // However the nap falls, energy follows the time spent asleep
+    assert_eq!(nap(0, 59 * 60), (20, 99));
+    assert_eq!(nap(HOUR / 2, 3 * HOUR / 2), (20, 98));
+    assert_eq!(nap(0, HOUR), (20, 99));
+    assert_eq!(nap(0, 2 * HOUR), (30, 99));
+
+    // Naps that end before the period is over add up
+    let mut state = pet_with(100, 100, 10);
+    for minute in [0, 20, 40] {
+        perform(&config, &mut state, Action::Sleep, minute * 60).unwrap();
+        perform(&config, &mut state, Action::Wake, (minute + 15) * 60).unwrap();
+    }
+    assert_eq!(state.napped, 45 * 60);
+    decay(&config, &mut state, HOUR);
+    assert_eq!(state.energy, 18);
+    assert_eq!(state.napped, 0);
+}
+
+#[test]
+fn test_decay_starvation() {
+    let config = GameConfig::ageless();
+
+    // Sick right away at 9 hunger (-2 health an hour), starving from hour 10
+    // on (-12 an hour)
+    let mut state = pet_with(10, 100, 100);
+    assert_eq!(decay(&config, &mut state, 15 * HOUR), None);
+    assert_eq!(state.health, 10);
+    assert!(state.is_sick);
+
+    let mut state = pet_with(10, 100, 100);
+    assert_eq!(
+        decay(&config, &mut state, 16 * HOUR),
+        Some(DeathCause::Starvation)
+    );
+    assert!(!state.is_alive);
+    assert_eq!(state.cause_of_death, Some(DeathCause::Starvation));
+    assert_eq!(state.died_at, Some(16 * HOUR));
+    assert_eq!(state.hunger, 0);
+    assert_eq!(state.health, 0);
+}
+
+#[test]
+fn test_death_time_does_not_depend_on_observation() {
+    let config = GameConfig::ageless();
+
+    for observed_at in [16 * HOUR, 17 * HOUR - 1, 40 * HOUR, u64::MAX] {
+        let mut state = pet_with(10, 100, 100);
+        decay(&config, &mut state, observed_at);
+        assert_eq!(state.died_at, Some(16 * HOUR));
+    }
+
+    // Nor on how often the pet was looked at before
+    let mut state = pet_with(10, 100, 100);
+    for hour in 1..=20 {
+        decay(&config, &mut state, hour * HOUR);
+    }
+    assert_eq!(state.died_at, Some(16 * HOUR));
+}
+
+#[test]
+fn test_decay_sadness() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 5, 100);
+
+    assert_eq!(
+        decay(&config, &mut state, 16 * HOUR),
+        Some(DeathCause::Sadness)
+    );
+    assert!(!state.is_alive);
+    assert_eq!(state.cause_of_death, Some(DeathCause::Sadness));
+    assert_eq!(state.happiness, 0);
+    assert_eq!(state.hunger, 84);
+}
+
+#[test]
+fn test_decay_sickness() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 100, 100).with_health(10).sick();
+
+    assert_eq!(
+        decay(&config, &mut state, 5 * HOUR),
+        Some(DeathCause::Sickness)
+    );
+    assert_eq!(state.cause_of_death, Some(DeathCause::Sickness));
+    assert_eq!(state.hunger, 95);
+}
+
+#[test]
+fn test_decay_stops_at_death() {
+    let mut config = GameConfig::ageless();
+    config.stages.adult.hunger_decay = u32::MAX;
+    let mut state = pet_with(100, 100, 100);
+
+    // Starving from the first hour, the pet lasts 9 hours however long the gap
+    assert_eq!(
+        decay(&config, &mut state, u64::MAX),
+        Some(DeathCause::Starvation)
+    );
+    assert_eq!(state.hunger, 0);
+    assert_eq!(state.happiness, 96);
+    assert_eq!(state.health, 0);
+    // Frozen at the time of death
+    assert_eq!(state.last_updated, 9 * HOUR);
+}
+
+#[test]
+fn test_low_stats_cause_sickness() {
+    let config = GameConfig::ageless();
+
+    let mut state = pet_with(22, 100, 100);
+    decay(&config, &mut state, HOUR);
+    assert!(!state.is_sick);
+    decay(&config, &mut state, 2 * HOUR);
+    assert!(state.is_sick);
+    assert_eq!(state.health, 98);
+
+    let mut state = pet_with(100, 21, 100);
+    decay(&config, &mut state, 2 * HOUR);
+    assert!(state.is_sick);
+}
+
+#[test]
+fn test_health_recovers_while_healthy() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 100, 100).with_health(50);
+
+    decay(&config, &mut state, 10 * HOUR);
+    assert_eq!(state.health, 60);
+
+    decay(&config, &mut state, 40 * HOUR);
+    assert_eq!(state.health, 90);
+
+    decay(&config, &mut state, 60 * HOUR);
+    assert_eq!(state.health, 100);
+}
+
+#[test]
+fn test_digestion_makes_a_mess() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(50, 100, 100).fed(0);
+
+    // -5 for each of the 2 hours spent digesting, then back to -1 per 2 hours
+    decay(&config, &mut state, 4 * HOUR);
+    assert_eq!(state.cleanliness, 89);
+
+    decay(&config, &mut state, 10 * HOUR);
+    assert_eq!(state.cleanliness, 86);
+}
+
+#[test]
+fn test_dirty_pet_gets_unhappy_and_sick() {
+    let config = GameConfig::ageless();
+
+    let mut state = pet_with(100, 100, 100).with_cleanliness(31);
+    decay(&config, &mut state, HOUR);
+    assert_eq!(state.happiness, 100);
+    decay(&config, &mut state, 3 * HOUR);
+    // Regular decay plus 1 for each of the 2 hours spent at the dirty threshold
+    assert_eq!(state.cleanliness, 30);
+    assert_eq!(state.happiness, 97);
+    assert!(!state.is_sick);
+
+    let mut state = pet_with(100, 100, 100).with_cleanliness(21);
+    decay(&config, &mut state, 2 * HOUR);
+    assert_eq!(state.cleanliness, 20);
+    assert!(state.is_sick);
+}
+
+#[test]
+fn test_clean() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(50, 50, 50).with_cleanliness(10).sick();
+
+    // Cleaning restores hygiene but leaves curing to medicine
+    perform(&config, &mut state, Action::Clean, 1).unwrap();
+    let expected = pet_with(50, 50, 50).sick();
+    assert_eq!(state, expected);
+
+    // Cleaning has no cooldown
+    perform(&config, &mut state, Action::Clean, 1).unwrap();
+    assert_eq!(state, expected);
+}
+
+#[test]
+fn test_life_stages_follow_age() {
+    let config = GameConfig::default();
+    let stage_at = |age| life_stage(&config, &pet_with(100, 100, 100), age);
+
+    assert_eq!(stage_at(0), LifeStage::Egg);
+    assert_eq!(stage_at(HOUR - 1), LifeStage::Egg);
+    assert_eq!(stage_at(HOUR), LifeStage::Baby);
+    assert_eq!(stage_at(DAY), LifeStage::Child);
+    assert_eq!(stage_at(3 * DAY), LifeStage::Teen);
+    assert_eq!(stage_at(7 * DAY), LifeStage::Adult);
+    assert_eq!(stage_at(60 * DAY), LifeStage::Elder);
+    assert_eq!(stage_at(u64::MAX), LifeStage::Elder);
+}
+
+#[test]
+fn test_eggs_neither_decay_nor_accept_actions() {
+    let config = GameConfig::default();
+    let mut state = PetState::new(&config, 0);
+    assert_eq!(state.stage, LifeStage::Egg);
+
+    for action in [Action::Feed, Action::Play, Action::Sleep, Action::Clean] {
+        assert_eq!(
+            perform(&config, &mut state, action, HOUR - 1),
+            Err(Error::NotAllowedAtStage)
+        );
+    }
+
+    // Hatching an hour in, then decaying at the faster baby rates
+    assert_eq!(decay(&config, &mut state, 3 * HOUR), None);
+    assert_eq!(state.stage, LifeStage::Baby);
+    assert_eq!(state.hunger, 96);
+    assert_eq!(state.happiness, 98);
+}
+
+#[test]
+fn test_only_grown_pets_work() {
+    let config = GameConfig::default();
+    let mut state = PetState::new(&config, 0);
+
+    assert_eq!(
+        perform(&config, &mut state, Action::Work, HOUR),
+        Err(Error::NotAllowedAtStage)
+    );
+    perform(&config, &mut state, Action::Play, HOUR).unwrap();
+
+    perform(&config, &mut state, Action::Work, DAY).unwrap();
+}
+
+#[test]
+fn test_decay_stops_at_old_age() {
+    let config = GameConfig::default();
+    let mut state = pet_with(100, 100, 100).with_last_updated(89 * DAY);
+
+    assert_eq!(
+        decay(&config, &mut state, 100 * DAY),
+        Some(DeathCause::OldAge)
+    );
+    assert_eq!(state.died_at, Some(90 * DAY));
+    assert_eq!(state.stage, LifeStage::Elder);
+    // Frozen at the end of the last day, elders lose a point of happiness an hour
+    assert_eq!(state.hunger, 76);
+    assert_eq!(state.happiness, 76);
+}
+
+#[test]
+fn test_care_grades() {
+    let config = GameConfig::default();
+    let grade = |missed_feedings, average_happiness: u32| {
+        let mut state = pet_with(100, 100, 100);
+        state.care = CareMetrics {
+            missed_feedings,
+            times_worked: 0,
+            happiness_total: u64::from(average_happiness) * 10,
+            periods: 10,
+        };
+        care_grade(&config, &state)
+    };
+
+    assert_eq!(grade(0, 70), CareGrade::Great);
+    assert_eq!(grade(1, 100), CareGrade::Fair);
+    assert_eq!(grade(0, 69), CareGrade::Fair);
+    assert_eq!(grade(2, 40), CareGrade::Fair);
+    assert_eq!(grade(3, 100), CareGrade::Poor);
+    assert_eq!(grade(0, 39), CareGrade::Poor);
+
+    // Without a full period in the stage the current happiness counts
+    let state = pet_with(100, 30, 100);
+    assert_eq!(care_grade(&config, &state), CareGrade::Poor);
+}
+
+#[test]
+fn test_evolution_table() {
+    assert_eq!(
+        evolved_form(LifeStage::Baby, CareGrade::Poor, true),
+        PetForm::Blob
+    );
+    assert_eq!(
+        evolved_form(LifeStage::Child, CareGrade::Great, false),
+        PetForm::Sprout
+    );
+    assert_eq!(
+        evolved_form(LifeStage::Teen, CareGrade::Poor, false),
+        PetForm::Grump
+    );
+    assert_eq!(
+        evolved_form(LifeStage::Adult, CareGrade::Fair, false),
+        PetForm::Buddy
+    );
+    // Hard work shapes adults, unless they were neglected
+    assert_eq!(
+        evolved_form(LifeStage::Adult, CareGrade::Great, true),
+        PetForm::Workhorse
+    );
+    assert_eq!(
+        evolved_form(LifeStage::Adult, CareGrade::Poor, true),
+        PetForm::Gremlin
+    );
+    assert_eq!(
+        evolved_form(LifeStage::Elder, CareGrade::Great, true),
+        PetForm::Sage
+    );
+}
+
+#[test]
+fn test_evolution_follows_care() {
+    let config = GameConfig::default();
+    let mut state = PetState::new(&config, 0);
+    assert_eq!(state.form, PetForm::Egg);
+
+    let mut evolutions = Vec::new();
+    decay_with(&config, &mut state, 2 * HOUR, |evolution| {
+        evolutions.push(evolution)
+    });
+    assert_eq!(
+        evolutions,
+        [Evolution {
+            stage: LifeStage::Baby,
+            form: PetForm::Blob,
+            evolved_at: HOUR,
+            missed_feedings: 0,
+            average_happiness: 100,
+            times_worked: 0,
+        }]
+    );
+    // The care record restarts with the stage
+    assert_eq!(state.care.periods, 1);
+
+    // A baby left to go hungry grows into a grub
+    state.care.missed_feedings = 3;
+    evolutions.clear();
+    decay_with(&config, &mut state, DAY + HOUR, |evolution| {
+        evolutions.push(evolution)
+    });
+    assert_eq!(evolutions.len(), 1);
+    assert_eq!(evolutions[0].stage, LifeStage::Child);
+    assert_eq!(evolutions[0].form, PetForm::Grub);
+    assert_eq!(evolutions[0].evolved_at, DAY);
+    assert_eq!(evolutions[0].missed_feedings, 3);
+    assert_eq!(state.form, PetForm::Grub);
+    assert_eq!(state.care.missed_feedings, 0);
+}
+
+#[test]
+fn test_long_gaps_go_through_every_stage() {
+    let config = GameConfig::default();
+    let mut state = PetState::new(&config, 0);
+
+    let mut stages = Vec::new();
+    decay_with(&config, &mut state, 8 * DAY, |evolution| {
+        stages.push(evolution.stage)
+    });
+    assert!(!state.is_alive);
+    // The pet starved as a teen, it never grew any older
+    assert_eq!(stages, [LifeStage::Baby, LifeStage::Child, LifeStage::Teen]);
+    assert_eq!(state.stage, LifeStage::Teen);
+}
+
+#[test]
+fn test_missed_feedings_are_counted_once_per_hungry_spell() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(42, 100, 100);
+
+    decay(&config, &mut state, 5 * HOUR);
+    assert_eq!(state.care.missed_feedings, 1);
+    assert_eq!(state.care.periods, 5);
+    assert_eq!(state.care.happiness_total, 100 + 99 + 99 + 98 + 98);
+
+    perform(&config, &mut state, Action::Feed, 5 * HOUR).unwrap();
+    decay(&config, &mut state, 40 * HOUR);
+    assert_eq!(state.care.missed_feedings, 2);
+}
+
+#[test]
+fn test_old_age_strikes_mid_period() {
+    let mut config = GameConfig::default();
+    config.stages.elder.ends_at = 90 * DAY + 1;
+
+    let mut state = pet_with(100, 100, 100).with_last_updated(90 * DAY);
+    assert_eq!(
+        decay(&config, &mut state, 91 * DAY),
+        Some(DeathCause::OldAge)
+    );
+    assert_eq!(state.died_at, Some(90 * DAY + 1));
+}
+
+#[test]
+fn test_actions() {
+    // Actions change stats right away but leave the decay clock alone
+    let config = GameConfig::ageless();
+    let mut state = pet_with(50, 50, 50);
+
+    perform(&config, &mut state, Action::Feed, 1).unwrap();
+    let expected = pet_with(80, 50, 50).fed(1);
+    assert_eq!(state, expected);
+
+    perform(&config, &mut state, Action::Play, 2).unwrap();
+    let expected = pet_with(80, 70, 35).fed(1).played(2);
+    assert_eq!(state, expected);
+
+    // Sleep gives no energy by itself, it comes back while the pet rests
+    perform(&config, &mut state, Action::Sleep, 3).unwrap();
+    assert_eq!(state, expected.asleep(3));
+
+    // Waking up only keeps track of how long the nap lasted
+    perform(&config, &mut state, Action::Wake, 4).unwrap();
+    assert_eq!(state, expected.napped(1));
+
+    perform(&config, &mut state, Action::Work, 5).unwrap();
+    let expected = pet_with(80, 60, 15).fed(1).played(2).napped(1).worked(5);
+    assert_eq!(state, expected);
+}
+
+#[test]
+fn test_actions_cap_at_max_stat() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(90, 95, 80);
+
+    perform(&config, &mut state, Action::Feed, 0).unwrap();
+    perform(&config, &mut state, Action::Play, 0).unwrap();
+    assert_eq!(state, pet_with(100, 100, 65).fed(0).played(0));
+}
+
+#[test]
+fn test_actions_cool_down() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(50, 50, 100);
+
+    perform(&config, &mut state, Action::Feed, 0).unwrap();
+    perform(&config, &mut state, Action::Work, 0).unwrap();
+    let cooling = state;
+
+    assert_eq!(
+        perform(&config, &mut state, Action::Feed, 1799),
+        Err(Error::ActionOnCooldown)
+    );
+    assert_eq!(
+        perform(&config, &mut state, Action::Work, 1800),
+        Err(Error::ActionOnCooldown)
+    );
+    assert_eq!(state, cooling);
+
+    // Each action keeps its own clock
+    assert_eq!(cooldown_remaining(&config, &state, Action::Feed, 600), 1200);
+    assert_eq!(cooldown_remaining(&config, &state, Action::Play, 600), 0);
+    assert_eq!(cooldown_remaining(&config, &state, Action::Work, 600), 3000);
+
+    perform(&config, &mut state, Action::Feed, 1800).unwrap();
+    perform(&config, &mut state, Action::Work, 3600).unwrap();
+    assert_eq!(state.hunger, 100);
+    assert_eq!(state.energy, 60);
+}
+
+#[test]
+fn test_zero_cooldown_allows_repeats() {
+    let config = GameConfig {
+        work_cooldown: 0,
+        ..GameConfig::ageless()
+    };
+    let mut state = pet_with(100, 100, 100);
+
+    perform(&config, &mut state, Action::Work, 7).unwrap();
+    perform(&config, &mut state, Action::Work, 7).unwrap();
+    assert_eq!(state.energy, 60);
+    assert_eq!(cooldown_remaining(&config, &state, Action::Work, 7), 0);
+}
+
+#[test]
+fn test_sleeping_pet_only_accepts_wake() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(50, 50, 50).asleep(0);
+    let sleeping = state;
+
+    for action in [Action::Feed, Action::Play, Action::Work, Action::Clean] {
+        assert_eq!(
+            perform(&config, &mut state, action, 1),
+            Err(Error::PetAsleep)
+        );
+    }
+    assert_eq!(
+        perform(&config, &mut state, Action::Sleep, 1),
+        Err(Error::AlreadyAsleep)
+    );
+    assert_eq!(state, sleeping);
+
+    perform(&config, &mut state, Action::Wake, 1).unwrap();
+    assert_eq!(
+        perform(&config, &mut state, Action::Wake, 2),
+        Err(Error::NotAsleep)
+    );
+    assert_eq!(state, pet_with(50, 50, 50).napped(1));
+}
+
+#[test]
+fn test_overfeeding_threshold() {
+    let config = GameConfig::ageless();
+
+    let mut state = pet_with(90, 50, 50);
+    perform(&config, &mut state, Action::Feed, 0).unwrap();
+    assert_eq!((state.hunger, state.happiness), (100, 50));
+
+    let mut state = pet_with(91, 50, 50);
+    perform(&config, &mut state, Action::Feed, 0).unwrap();
+    assert_eq!((state.hunger, state.happiness), (100, 40));
+
+    let mut state = pet_with(100, 5, 50);
+    perform(&config, &mut state, Action::Feed, 0).unwrap();
+    assert_eq!((state.hunger, state.happiness), (100, 0));
+}
+
+#[test]
+fn test_play_streak_diminishes_returns() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 0, 100);
+    let mut now = 0;
+
+    for (streak, gain) in [(0, 20), (1, 10), (2, 6), (3, 5), (4, 4)] {
+        let before = state.happiness;
+        perform(&config, &mut state, Action::Play, now).unwrap();
+        assert_eq!(state.play_streak, streak);
+        assert_eq!(state.happiness - before, gain);
+        now += config.play_cooldown;
+    }
+
+    // A long enough break resets the streak
+    now += config.play_streak_window;
+    let before = state.happiness;
+    perform(&config, &mut state, Action::Play, now).unwrap();
+    assert_eq!(state.play_streak, 0);
+    assert_eq!(state.happiness - before, 20);
+}
+
+#[test]
+fn test_play_streak_window_boundary() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 0, 100);
+
+    perform(&config, &mut state, Action::Play, 0).unwrap();
+    perform(
+        &config,
+        &mut state,
+        Action::Play,
+        config.play_streak_window - 1,
+    )
+    .unwrap();
+    assert_eq!(state.play_streak, 1);
+
+    let last = config.play_streak_window - 1;
+    perform(
+        &config,
+        &mut state,
+        Action::Play,
+        last + config.play_streak_window,
+    )
+    .unwrap();
+    assert_eq!(state.play_streak, 0);
+}
+
+#[test]
+fn test_overfeeding_causes_sickness() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(95, 100, 100);
+
+    perform(&config, &mut state, Action::Feed, 0).unwrap();
+    perform(&config, &mut state, Action::Feed, HOUR).unwrap();
+    assert_eq!(state.overfeed_count, 2);
+    assert!(!state.is_sick);
+
+    // A proper meal in between resets the count
+    state.hunger = 50;
+    perform(&config, &mut state, Action::Feed, 2 * HOUR).unwrap();
+    assert_eq!(state.overfeed_count, 0);
+
+    // Back to full after one more meal, then overfed three times
+    for hour in 3..7 {
+        perform(&config, &mut state, Action::Feed, hour * HOUR).unwrap();
+    }
+    assert_eq!(state.overfeed_count, 3);
+    assert!(state.is_sick);
+}
+
+#[test]
+fn test_medicine() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 100, 100).with_health(30);
+
+    assert_eq!(
+        perform(&config, &mut state, Action::Medicine, 0),
+        Err(Error::NotSick)
+    );
+
+    let mut state = state.sick();
+    state.overfeed_count = 3;
+    perform(&config, &mut state, Action::Medicine, 0).unwrap();
+    assert!(!state.is_sick);
+    assert_eq!(state.overfeed_count, 0);
+    assert_eq!(state.health, 50);
+}
+
+#[test]
+fn test_items_replace_the_usual_gain() {
+    let config = GameConfig::ageless();
+    let cake = StatEffects {
+        hunger: 35,
+        happiness: 15,
+        health: -5,
+        ..StatEffects::default()
+    };
+
+    let mut state = pet_with(40, 50, 50);
+    perform_with(&config, &mut state, Action::Feed, 0, Some(cake)).unwrap();
+    assert_eq!((state.hunger, state.happiness, state.health), (75, 65, 95));
+    assert_eq!(state.last_fed, Some(0));
+
+    // A toy decides the energy cost too
+    let ball = StatEffects {
+        happiness: 25,
+        energy: -10,
+        ..StatEffects::default()
+    };
+    let mut state = pet_with(100, 50, 50);
+    perform_with(&config, &mut state, Action::Play, 0, Some(ball)).unwrap();
+    assert_eq!((state.happiness, state.energy), (75, 40));
+    assert_eq!(state.last_played, Some(0));
+
+    let potion = StatEffects {
+        health: 10,
+        ..StatEffects::default()
+    };
+    let mut state = pet_with(100, 100, 100).with_health(30).sick();
+    perform_with(&config, &mut state, Action::Medicine, 0, Some(potion)).unwrap();
+    assert!(!state.is_sick);
+    assert_eq!(state.health, 40);
+}
+
+#[test]
+fn test_item_effects_stay_within_bounds() {
+    let config = GameConfig::ageless();
+    let feast = StatEffects {
+        hunger: i32::MAX,
+        happiness: i32::MIN,
+        energy: -200,
+        health: 200,
+        cleanliness: -1,
+    };
+    let mut state = pet_with(50, 50, 50);
+
+    perform_with(&config, &mut state, Action::Feed, 0, Some(feast)).unwrap();
+    assert_eq!(
+        (
+            state.hunger,
+            state.happiness,
+            state.energy,
+            state.health,
+            state.cleanliness
+        ),
+        (config.max_stat, 0, 0, config.max_stat, 99)
+    );
+}
+
+#[test]
+fn test_items_never_kill() {
+    let config = GameConfig::ageless();
+    let cake = StatEffects {
+        hunger: 35,
+        health: -5,
+        ..StatEffects::default()
+    };
+    let mut state = pet_with(50, 50, 50).with_health(5);
+
+    perform_with(&config, &mut state, Action::Feed, 0, Some(cake)).unwrap();
+    assert_eq!(state.health, 1);
+    assert!(state.is_alive);
+
+    // The next period still heals or hurts the pet as usual
+    decay(&config, &mut state, HOUR);
+    assert_eq!(state.health, 2);
+
+    let poison = StatEffects {
+        health: i32::MIN,
+        ..StatEffects::default()
+    };
+    perform_with(&config, &mut state, Action::Feed, HOUR, Some(poison)).unwrap();
+    assert_eq!(state.health, 1);
+}
+
+#[test]
+fn test_items_do_not_skip_the_rules() {
+    let config = GameConfig::ageless();
+    let apple = StatEffects {
+        hunger: 20,
+        ..StatEffects::default()
+    };
+    let mut state = pet_with(50, 50, 50).fed(0);
+
+    assert_eq!(
+        perform_with(&config, &mut state, Action::Feed, 1, Some(apple)),
+        Err(Error::ActionOnCooldown)
+    );
+    assert_eq!(state, pet_with(50, 50, 50).fed(0));
+
+    // Treats still count as overfeeding
+    let mut state = pet_with(95, 50, 50);
+    perform_with(&config, &mut state, Action::Feed, 0, Some(apple)).unwrap();
+    assert_eq!(state.overfeed_count, 1);
+    assert_eq!(state.happiness, 40);
+}
+
+#[test]
+fn test_work_needs_energy() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 100, 19);
+
+    assert_eq!(
+        perform(&config, &mut state, Action::Work, 5),
+        Err(Error::InsufficientEnergy)
+    );
+    assert_eq!(state, pet_with(100, 100, 19));
+}
+
+#[test]
+fn test_dead_pet_is_left_alone() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(0, 40, 40);
+    state.is_alive = false;
+    let dead = state;
+
+    assert_eq!(decay(&config, &mut state, 100 * HOUR), None);
+    assert_eq!(
+        perform(&config, &mut state, Action::Feed, 100 * HOUR),
+        Err(Error::PetDead)
+    );
+    assert_eq!(state, dead);
+}
+
+#[test]
+fn test_revive() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(10, 100, 100).sick();
+    decay(&config, &mut state, 40 * HOUR);
+    assert_eq!(state.died_at, Some(16 * HOUR));
+
+    // Back at 50 everywhere, counting periods from the start of the current one
+    revive(&config, &mut state, 20 * HOUR + 30 * 60).unwrap();
+    assert!(state.is_alive);
+    assert_eq!(state.cause_of_death, None);
+    assert_eq!(state.died_at, None);
+    assert_eq!(state.hunger, 50);
+    assert_eq!(state.happiness, 50);
+    assert_eq!(state.energy, 50);
+    assert_eq!(state.health, 50);
+    assert_eq!(state.cleanliness, 50);
+    assert!(!state.is_sick);
+    assert_eq!(state.last_updated, 20 * HOUR);
+
+    // Time spent dead didn't count against it
+    decay(&config, &mut state, 21 * HOUR);
+    assert_eq!(state.hunger, 49);
+    assert_eq!(
+        revive(&config, &mut state, 21 * HOUR),
+        Err(Error::PetNotDead)
+    );
+}
+
+#[test]
+fn test_revival_window_boundary() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(10, 100, 100);
+    decay(&config, &mut state, 16 * HOUR);
+    let dead = state;
+
+    assert_eq!(
+        revive(&config, &mut state, 16 * HOUR + DAY + 1),
+        Err(Error::RevivalWindowExpired)
+    );
+    assert_eq!(state, dead);
+    revive(&config, &mut state, 16 * HOUR + DAY).unwrap();
+    assert!(state.is_alive);
+}
+
+#[test]
+fn test_old_age_is_not_revivable() {
+    let config = GameConfig::default();
+    let mut state = pet_with(100, 100, 100).with_last_updated(89 * DAY);
+    decay(&config, &mut state, 90 * DAY);
+    let dead = state;
+
+    assert_eq!(
+        revive(&config, &mut state, 90 * DAY),
+        Err(Error::NotRevivable)
+    );
+    assert_eq!(state, dead);
+}
+
+/* -------------------------------------------------------------------------- */
+/*                              PROPERTY TESTS                                */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn prop_stats_stay_within_bounds() {
+    let config = GameConfig::ageless();
+
+    for seed in 1..=200 {
+        let mut rng = Rng(seed);
+        let mut state = PetState::new(&config, 0);
+        let mut now = 0;
+
+        for _ in 0..500 {
+            now += rng.below(6 * HOUR);
+            decay(&config, &mut state, now);
+            let _ = perform(&config, &mut state, rng.action(), now);
+
+            assert!(state.hunger <= config.max_stat);
+            assert!(state.happiness <= config.max_stat);
+            assert!(state.energy <= config.max_stat);
+            assert!(state.health <= config.max_stat);
+        }
+    }
+}
+
+#[test]
+fn prop_decay_never_raises_stats() {
+    let config = GameConfig::ageless();
+
+    // Only holds for awake pets, sleeping ones recover energy over time
+    for seed in 1..=200 {
+        let mut rng = Rng(seed);
+        let before = pet_with(
+            rng.below(101) as u32,
+            rng.below(101) as u32,
+            rng.below(101) as u32,
+        );
+        let mut after = before;
+        decay(&config, &mut after, rng.below(400 * HOUR));
+
+        assert!(after.hunger <= before.hunger);
+        assert!(after.happiness <= before.happiness);
+        assert!(after.energy <= before.energy);
+        assert!(after.last_updated >= before.last_updated);
+    }
+}
+
+#[test]
+fn prop_death_is_final_and_reported_once() {
+    let config = GameConfig::ageless();
+
+    for seed in 1..=200 {
+        let mut rng = Rng(seed);
+        let mut state = PetState::new(&config, 0);
+        let mut now = 0;
+        let mut deaths = 0;
+
+        for _ in 0..300 {
+            now += rng.below(12 * HOUR);
+            if decay(&config, &mut state, now).is_some() {
+                deaths += 1;
+            }
+            let was_alive = state.is_alive;
+            let result = perform(&config, &mut state, rng.action(), now);
+            if !was_alive {
+                assert_eq!(result, Err(Error::PetDead));
+            }
+        }
+        assert!(deaths <= 1);
+        assert_eq!(deaths == 1, !state.is_alive);
+    }
+}
+
+#[test]
+fn prop_decay_is_independent_of_call_frequency() {
+    // Stage boundaries that fall in the middle of a period included
+    let long_periods = GameConfig {
+        decay_period: 5000,
+        ..GameConfig::default()
+    };
+    let mut late_child = GameConfig::default();
+    late_child.stages.baby.ends_at = DAY + 1800;
+
+    for config in [
+        GameConfig::default(),
+        GameConfig::ageless(),
+        long_periods,
+        late_child,
+    ] {
+        assert!(config.is_valid());
+        for seed in 1..=200 {
+            let mut rng = Rng(seed);
+            let end = rng.below(10 * DAY);
+
+            let mut once = PetState::new(&config, 0);
+            let mut once_evolutions = Vec::new();
+            decay_with(&config, &mut once, end, |evolution| {
+                once_evolutions.push(evolution)
+            });
+
+            // Looked at every few seconds to every few hours
+            let max_gap = 1 + rng.below(3 * HOUR);
+            let mut often = PetState::new(&config, 0);
+            let mut often_evolutions = Vec::new();
+            let mut now = 0;
+            while now < end {
+                now = (now + 1 + rng.below(max_gap)).min(end);
+                decay_with(&config, &mut often, now, |evolution| {
+                    often_evolutions.push(evolution)
+                });
+            }
+
+            assert_eq!(once, often);
+            assert_eq!(once_evolutions, often_evolutions);
+        }
+    }
+}
+
+#[test]
+fn prop_actions_keep_partial_periods() {
+    let config = GameConfig::default();
+
+    for seed in 1..=200 {
+        let mut rng = Rng(seed);
+        let mut plain = PetState::new(&config, 0);
+        let mut observed = plain;
+        let mut now = 0;
+
+        // The same actions at the same times, with extra decays in between
+        // for one of the pets only
+        for _ in 0..100 {
+            let next = now + rng.below(4 * HOUR);
+            for _ in 0..rng.below(4) {
+                now += rng.below(next - now + 1);
+                decay(&config, &mut observed, now);
+            }
+            now = next;
+
+            let action = rng.action();
+            decay(&config, &mut plain, now);
+            decay(&config, &mut observed, now);
+            assert_eq!(
+                perform(&config, &mut plain, action, now),
+                perform(&config, &mut observed, action, now)
+            );
+            assert_eq!(plain, observed);
+        }
+    }
+}
+
+#[test]
+fn test_frequent_reads_do_not_slow_decay() {
+    let config = GameConfig::ageless();
+    let mut state = pet_with(100, 100, 100);
+
+    // Every 1.9 hours used to throw away the partial period each time, and
+    // happiness never got 2 periods in a row to decay
+    for read in 1..=10 {
+        decay(&config, &mut state, read * 19 * HOUR / 10);
+    }
+    assert_eq!(state.hunger, 81);
+    assert_eq!(state.happiness, 91);
+    assert_eq!(state.last_updated, 19 * HOUR);
+}
+
+#[test]
+fn test_caretaker_simulation_over_a_million_ticks() {
+    let config = GameConfig::ageless();
+    let mut state = PetState::new(&config, 0);
+    let mut coins: i128 = 0;
+
+    // A diligent player checking in every hour for about 114 years
+    for tick in 1..=1_000_000u64 {
+        let now = tick * HOUR;
+        assert_eq!(decay(&config, &mut state, now), None);
+
+        let Some(action) = caretaker_action(&config, &state, now) else {
+            continue;
+        };
+        perform(&config, &mut state, action, now).unwrap();
+        if action == Action::Work {
+            coins += config.work_reward;
+        }
+    }
+
+    assert!(state.is_alive);
+    assert!(coins > 0);
+}
+
+#[test]
+fn test_well_cared_for_pet_dies_of_old_age() {
+    let config = GameConfig::default();
+    let mut state = PetState::new(&config, 0);
+    let lifespan = config.stages.lifespan();
+
+    let mut evolutions = Vec::new();
+    let mut now = 0;
+    let cause = loop {
+        now += HOUR;
+        let died = decay_with(&config, &mut state, now, |evolution| {
+            evolutions.push(evolution)
+        });
+        if let Some(cause) = died {
+            break cause;
+        }
+        if let Some(action) = caretaker_action(&config, &state, now) {
+            perform(&config, &mut state, action, now).unwrap();
+        }
+    };
+
+    assert_eq!(cause, DeathCause::OldAge);
+    assert_eq!(now, lifespan);
+    assert_eq!(state.stage, LifeStage::Elder);
+    assert!(state.health > 0);
+
+    // Evolving once into every stage, right when the pet reached its age
+    let stages = [
+        LifeStage::Baby,
+        LifeStage::Child,
+        LifeStage::Teen,
+        LifeStage::Adult,
+        LifeStage::Elder,
+    ];
+    // Pampered as a baby, the pet spends the rest of its life working
+    let forms = [
+        PetForm::Blob,
+        PetForm::Sprout,
+        PetForm::Scamp,
+        PetForm::Workhorse,
+        PetForm::Veteran,
+    ];
+    assert_eq!(evolutions.len(), stages.len());
+    for ((evolution, stage), form) in evolutions.iter().zip(stages).zip(forms) {
+        assert_eq!(evolution.stage, stage);
+        assert_eq!(evolution.form, form);
+        assert_eq!(evolution.evolved_at, config.stages.starts_at(stage));
+        assert_eq!(evolution.missed_feedings, 0);
+    }
+}
+
+// What a diligent player does when checking in, `None` when the pet needs
+// nothing or is not old enough for anything
+fn caretaker_action(config: &GameConfig, state: &PetState, now: u64) -> Option<Action> {
+    let rules = config.stages.rules(life_stage(config, state, now))?;
+    let action = if state.asleep_since.is_some() {
+        if state.energy < config.max_stat {
+            return None;
+        }
+        Action::Wake
+    } else if state.hunger < 50 {
+        Action::Feed
+    } else if state.happiness < 50 {
+        Action::Play
+    } else if state.cleanliness < 50 {
+        Action::Clean
+    } else if state.energy < 40 {
+        Action::Sleep
+    } else if rules.can_work {
+        Action::Work
+    } else {
+        return None;
+    };
+    Some(action)
+}
+
+impl PetState {
+    fn born(mut self, at: u64) -> Self {
+        self.born_at = at;
+        self
+    }
+
+    fn with_last_updated(mut self, last_updated: u64) -> Self {
+        self.last_updated = last_updated;
+        self
+    }
+
+    fn asleep(mut self, since: u64) -> Self {
+        self.asleep_since = Some(since);
+        self
+    }
+
+    fn with_health(mut self, health: u32) -> Self {
+        self.health = health;
+        self
+    }
+
+    fn with_cleanliness(mut self, cleanliness: u32) -> Self {
+        self.cleanliness = cleanliness;
+        self
+    }
+
+    fn sick(mut self) -> Self {
+        self.is_sick = true;
+        self
+    }
+
+    fn napped(mut self, seconds: u64) -> Self {
+        self.napped = seconds;
+        self
+    }
+
+    fn fed(mut self, at: u64) -> Self {
+        self.last_fed = Some(at);
+        self
+    }
+
+    fn played(mut self, at: u64) -> Self {
+        self.last_played = Some(at);
+        self
+    }
+
+    fn worked(mut self, at: u64) -> Self {
+        self.last_worked = Some(at);
+        self.care.times_worked += 1;
+        self
+    }
+}
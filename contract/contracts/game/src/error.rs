@@ -0,0 +1,49 @@
+use soroban_sdk::contracterror;
+
+/// Errors returned by the contract. The numeric codes are part of the
+/// public interface, so existing variants must never be renumbered.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    PetNotFound = 1,
+    PetDead = 2,
+    // No longer returned now that owners can keep several pets
+    PetAlreadyExists = 3,
+    InsufficientEnergy = 4,
+    InsufficientCoins = 5,
+    UnsupportedSchemaVersion = 6,
+    InvalidConfig = 7,
+    NotOwner = 8,
+    TooManyPets = 9,
+    PetAsleep = 10,
+    AlreadyAsleep = 11,
+    NotAsleep = 12,
+    ActionOnCooldown = 13,
+    NotSick = 14,
+    NotAllowedAtStage = 15,
+    PetNotDead = 16,
+    RevivalWindowExpired = 17,
+    // Pets that died of old age are gone for good
+    NotRevivable = 18,
+    NegativeAmount = 19,
+    InsufficientAllowance = 20,
+    // Allowances must outlive the current ledger unless they are being cleared
+    InvalidExpirationLedger = 21,
+    // Coins and gifts must go to someone else
+    SelfTransfer = 22,
+    BalanceOverflow = 23,
+    UnknownItem = 24,
+    ItemAlreadyOwned = 25,
+    // The admin hasn't put a coin price on the asset
+    AssetNotAccepted = 26,
+    InvalidItem = 27,
+    OutOfStock = 28,
+    // The owner has no such item in their inventory
+    ItemNotOwned = 29,
+    NotEquippable = 30,
+    SlotEmpty = 31,
+    InvalidQuantity = 32,
+    // The item can't be used that way, like feeding a pet a ball
+    UnsuitableItem = 33,
+}
@@ -0,0 +1,286 @@
+//! Events published by the contract.
+//!
+//! Every event starts with a fixed topic naming the event, followed by the
+//! addresses involved and, for pet events, the pet ID so indexers can filter
+//! by player:
+//!
+//! | Event                | Topics                                           | Data                        |
+//! |----------------------|--------------------------------------------------|-----------------------------|
+//! | `pet_created`        | `["pet_created", owner, pet_id]`                 | `{ name, birthdate }`       |
+//! | `action_performed`   | `["action_performed", owner, pet_id, action]`    | `{ before, after }`         |
+//! | `coins_earned`       | `["coins_earned", owner]`                        | `{ amount, balance }`       |
+//! | `coins_spent`        | `["coins_spent", owner]`                         | `{ amount, balance }`       |
+//! | `coins_purchased`    | `["coins_purchased", buyer, asset]`              | `{ amount, cost, balance }` |
+//! | `accessory_minted`   | `["accessory_minted", owner, pet_id, accessory]` | `{ price }`                 |
+//! | `item_bought`        | `["item_bought", owner, item]`                   | `{ quantity, cost }`        |
+//! | `item_equipped`      | `["item_equipped", owner, pet_id, item]`         | `{ slot }`                  |
+//! | `item_unequipped`    | `["item_unequipped", owner, pet_id, item]`       | `{ slot }`                  |
+//! | `item_used`          | `["item_used", owner, pet_id, item]`             | `{ action }`                |
+//! | `item_gifted`        | `["item_gifted", from, owner, pet_id, item]`     | `{ price }`                 |
+//! | `pet_evolved`        | `["pet_evolved", owner, pet_id]`                 | `{ evolution }`             |
+//! | `pet_died`           | `["pet_died", owner, pet_id]`                    | `{ cause, died_at, stats }` |
+//! | `pet_revived`        | `["pet_revived", owner, pet_id]`                 | `{ revivals, stats }`       |
+//! | `pet_removed`        | `["pet_removed", owner, pet_id]`                 | `{ was_alive }`             |
+//! | `treasury_withdrawn` | `["treasury_withdrawn", asset, to]`              | `{ amount }`                |
+//!
+//! `action` is one of `feed`, `play`, `sleep`, `wake`, `work`, `medicine` or
+//! `clean`, `item` is the ID of a catalog item and `accessory` is always
+//! `glasses`, the only item `mint_glasses` sells.
+//!
+//! Coins are a SEP-41 token, so every balance change is also reported with
+//! the standard token events wallets and indexers already understand:
+//!
+//! | Event      | Topics                       | Data                                   |
+//! |------------|------------------------------|----------------------------------------|
+//! | `mint`     | `["mint", to]`               | `amount`                               |
+//! | `burn`     | `["burn", from]`             | `amount`                               |
+//! | `transfer` | `["transfer", from, to]`     | `amount`, or `{ to_muxed_id, amount }` |
+//! | `approve`  | `["approve", from, spender]` | `[amount, expiration_ledger]`          |
+use soroban_sdk::{contractevent, contracttype, Address, String, Symbol};
+
+use crate::{CosmeticSlot, DeathCause, Evolution};
+
+/// Snapshot of a pet's stats, used to report the effect of an action.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PetStats {
+    pub hunger: u32,
+    pub happiness: u32,
+    pub energy: u32,
+    pub health: u32,
+    pub cleanliness: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PetCreated {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    pub name: String,
+    pub birthdate: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionPerformed {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    #[topic]
+    pub action: Symbol,
+    pub before: PetStats,
+    pub after: PetStats,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoinsEarned {
+    #[topic]
+    pub owner: Address,
+    pub amount: i128,
+    pub balance: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoinsSpent {
+    #[topic]
+    pub owner: Address,
+    pub amount: i128,
+    pub balance: i128,
+}
+
+#[contractevent(data_format = "single-value")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Mint {
+    #[topic]
+    pub to: Address,
+    pub amount: i128,
+}
+
+#[contractevent(data_format = "single-value")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Burn {
+    #[topic]
+    pub from: Address,
+    pub amount: i128,
+}
+
+#[contractevent(data_format = "single-value")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Transfer {
+    #[topic]
+    pub from: Address,
+    #[topic]
+    pub to: Address,
+    pub amount: i128,
+}
+
+/// Transfer to a muxed account, the ID tells the receiving service which of
+/// its users the coins are for.
+#[contractevent(topics = ["transfer"], data_format = "map")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransferMuxed {
+    #[topic]
+    pub from: Address,
+    #[topic]
+    pub to: Address,
+    pub to_muxed_id: u64,
+    pub amount: i128,
+}
+
+#[contractevent(data_format = "vec")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Approve {
+    #[topic]
+    pub from: Address,
+    #[topic]
+    pub spender: Address,
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+/// Coins bought with `cost` units of a Stellar asset, now held in the treasury.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoinsPurchased {
+    #[topic]
+    pub buyer: Address,
+    #[topic]
+    pub asset: Address,
+    pub amount: i128,
+    pub cost: i128,
+    pub balance: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccessoryMinted {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    #[topic]
+    pub accessory: Symbol,
+    pub price: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ItemBought {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub item: Symbol,
+    pub quantity: u32,
+    pub cost: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ItemEquipped {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    #[topic]
+    pub item: Symbol,
+    pub slot: CosmeticSlot,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ItemUnequipped {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    #[topic]
+    pub item: Symbol,
+    pub slot: CosmeticSlot,
+}
+
+/// An item used up by `action`, whose effect is reported by the
+/// `action_performed` event published alongside.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ItemUsed {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    #[topic]
+    pub item: Symbol,
+    pub action: Symbol,
+}
+
+/// An item bought by `from` for someone else's pet.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ItemGifted {
+    #[topic]
+    pub from: Address,
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    #[topic]
+    pub item: Symbol,
+    pub price: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PetEvolved {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    pub evolution: Evolution,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PetDied {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    pub cause: DeathCause,
+    pub died_at: u64,
+    pub stats: PetStats,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PetRevived {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    pub revivals: u32,
+    pub stats: PetStats,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PetRemoved {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub pet_id: u64,
+    pub was_alive: bool,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TreasuryWithdrawn {
+    #[topic]
+    pub asset: Address,
+    #[topic]
+    pub to: Address,
+    pub amount: i128,
+}
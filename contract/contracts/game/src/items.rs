@@ -0,0 +1,133 @@
+use soroban_sdk::{contracttype, symbol_short, vec, Env, String, Symbol, Vec};
+
+// Every deployment sells glasses, they were the game's first item
+pub const GLASSES: Symbol = symbol_short!("glasses");
+
+/// What an item is for. Food, toys and medicine are used up with `use_item`,
+/// food and toys also with `feed` and `play`.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ItemCategory {
+    // Worn by a pet in its cosmetic slot
+    Accessory,
+    Food,
+    Toy,
+    // Cures sickness like `give_medicine`
+    Medicine,
+}
+
+/// Where on the pet an accessory goes, each slot holds one item at a time.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum CosmeticSlot {
+    // Items that are not worn
+    None,
+    Head,
+    Face,
+    Body,
+}
+
+/// Change to each stat when the item is used, negative values lower it.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StatEffects {
+    pub hunger: i32,
+    pub happiness: i32,
+    pub energy: i32,
+    pub health: i32,
+    pub cleanliness: i32,
+}
+
+/// An entry of the shop catalog, managed by the admin with `set_item`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Item {
+    pub id: Symbol,
+    pub name: String,
+    pub category: ItemCategory,
+    // Coins per unit
+    pub price: i128,
+    // Units left for sale, `None` for an unlimited supply
+    pub stock: Option<u32>,
+    pub effects: StatEffects,
+    // `CosmeticSlot::None` for anything but accessories
+    pub slot: CosmeticSlot,
+}
+
+impl Item {
+    // What a fresh deployment sells
+    pub fn starter_catalog(env: &Env) -> Vec<Item> {
+        let item = |id: Symbol, name: &str, category, price, effects| Item {
+            id,
+            name: String::from_str(env, name),
+            category,
+            price,
+            stock: None,
+            effects,
+            slot: CosmeticSlot::None,
+        };
+        let effects = StatEffects::default();
+        vec![
+            env,
+            Item {
+                slot: CosmeticSlot::Face,
+                ..item(
+                    GLASSES,
+                    "Cool Glasses",
+                    ItemCategory::Accessory,
+                    50,
+                    effects,
+                )
+            },
+            item(
+                symbol_short!("apple"),
+                "Apple",
+                ItemCategory::Food,
+                5,
+                StatEffects {
+                    hunger: 20,
+                    health: 2,
+                    ..effects
+                },
+            ),
+            item(
+                symbol_short!("cake"),
+                "Cake",
+                ItemCategory::Food,
+                15,
+                StatEffects {
+                    hunger: 35,
+                    happiness: 15,
+                    health: -5,
+                    ..effects
+                },
+            ),
+            item(
+                symbol_short!("medicine"),
+                "Medicine",
+                ItemCategory::Medicine,
+                25,
+                StatEffects {
+                    health: 40,
+                    ..effects
+                },
+            ),
+            item(
+                symbol_short!("ball"),
+                "Ball",
+                ItemCategory::Toy,
+                10,
+                StatEffects {
+                    happiness: 25,
+                    energy: -10,
+                    ..effects
+                },
+            ),
+        ]
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let wearable = self.category == ItemCategory::Accessory;
+        self.price >= 0 && wearable == (self.slot != CosmeticSlot::None)
+    }
+}
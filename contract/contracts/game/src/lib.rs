@@ -1,31 +1,306 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, token::TokenClient, Address, BytesN, Env,
+    Map, String, Symbol, Vec,
+};
 
-// Define the maximum value for stats
+mod config;
+pub mod engine;
+mod error;
+pub mod events;
+mod items;
+mod storage;
+mod token;
+
+pub use config::{EvolutionRules, GameConfig, LifeStages, StageRules};
+pub use error::Error;
+pub use items::{CosmeticSlot, Item, ItemCategory, StatEffects};
+pub use storage::DataKey;
+
+use engine::{Action, PetState};
+use events::{
+    AccessoryMinted, ActionPerformed, CoinsEarned, CoinsPurchased, CoinsSpent, ItemBought,
+    ItemEquipped, ItemGifted, ItemUnequipped, ItemUsed, PetCreated, PetDied, PetEvolved,
+    PetRemoved, PetRevived, PetStats, Transfer, TreasuryWithdrawn,
+};
+use items::GLASSES;
+
+// Default maximum value for stats, see `GameConfig::max_stat`
 const MAX_STAT: u32 = 100;
 
+// Most graves returned by a single `get_graveyard` call
+const MAX_GRAVEYARD_PAGE: u32 = 50;
+
+// Most items returned by a single `list_items` call
+const MAX_ITEMS_PAGE: u32 = 50;
+
+// Layout version of the data this code reads and writes. Bump it whenever a
+// stored type changes shape and teach `migrate` how to convert older data.
+//
+// Version 2 keys pets by ID. Version 1, a single pet per owner stored under
+// their address, never shipped, so there is nothing to convert from it.
+const SCHEMA_VERSION: u32 = 2;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Pet {
+    pub id: u64,
     pub owner: Address,
     pub name: String,
     pub birthdate: u64,
     pub last_updated: u64,
     pub is_alive: bool,
+    pub status: LifeStatus,
+    // When the fatal period ended, not when the death was noticed
+    pub died_at: Option<u64>,
+    // Times the pet was brought back with `revive`
+    pub revivals: u32,
+    // Derived from `birthdate`, refreshed whenever the pet is loaded
+    pub stage: LifeStage,
+    // Chosen from the care received every time the pet enters a new stage
+    pub form: PetForm,
+    // How the pet has been looked after since it entered its current stage
+    pub care: CareMetrics,
 
     // Stats
     pub hunger: u32,
     pub happiness: u32,
     pub energy: u32,
+    pub health: u32,
+    pub cleanliness: u32,
+
+    // Sickness drains health until cured with `give_medicine`
+    pub is_sick: bool,
+    // Overfed meals in a row, too many of them make the pet sick
+    pub overfeed_count: u32,
+
+    // Set while the pet is sleeping: energy recovers over time, hunger slows
+    // down and only `wake` is accepted until the nap ends
+    pub asleep_since: Option<u64>,
+    // Seconds slept in naps that ended during the period in progress, so it
+    // only recovers energy for the time actually spent asleep
+    pub napped: u64,
 
-    // Customization
+    // Last time each action was performed, drives the cooldowns
+    pub last_fed: Option<u64>,
+    pub last_played: Option<u64>,
+    pub last_worked: Option<u64>,
+
+    // Plays in a row, each one shortly after the last, for diminishing returns
+    pub play_streak: u32,
+
+    // Customization: the accessory worn in each slot. `has_glasses` mirrors
+    // whether glasses are among them, for clients that predate the shop.
+    pub equipped: Map<CosmeticSlot, Symbol>,
     pub has_glasses: bool,
 }
 
+impl Pet {
+    fn accessories(&self) -> Vec<Symbol> {
+        self.equipped.values()
+    }
+
+    // Put `item` on in `slot`, returns what the pet wore there before
+    fn wear(&mut self, slot: CosmeticSlot, item: Symbol) -> Option<Symbol> {
+        let previous = self.equipped.get(slot);
+        self.equipped.set(slot, item);
+        self.has_glasses = self.equipped.values().contains(GLASSES);
+        previous
+    }
+
+    fn take_off(&mut self, slot: CosmeticSlot) -> Option<Symbol> {
+        let previous = self.equipped.get(slot);
+        self.equipped.remove(slot);
+        self.has_glasses = self.equipped.values().contains(GLASSES);
+        previous
+    }
+
+    fn stats(&self) -> PetStats {
+        PetStats {
+            hunger: self.hunger,
+            happiness: self.happiness,
+            energy: self.energy,
+            health: self.health,
+            cleanliness: self.cleanliness,
+        }
+    }
+
+    fn state(&self) -> PetState {
+        PetState {
+            born_at: self.birthdate,
+            stage: self.stage,
+            form: self.form,
+            care: self.care,
+            last_updated: self.last_updated,
+            is_alive: self.is_alive,
+            cause_of_death: match self.status {
+                LifeStatus::Alive => None,
+                LifeStatus::Dead(cause) => Some(cause),
+            },
+            died_at: self.died_at,
+            hunger: self.hunger,
+            happiness: self.happiness,
+            energy: self.energy,
+            health: self.health,
+            cleanliness: self.cleanliness,
+            is_sick: self.is_sick,
+            overfeed_count: self.overfeed_count,
+            asleep_since: self.asleep_since,
+            napped: self.napped,
+            last_fed: self.last_fed,
+            last_played: self.last_played,
+            last_worked: self.last_worked,
+            play_streak: self.play_streak,
+        }
+    }
+
+    fn set_state(&mut self, state: &PetState) {
+        self.stage = state.stage;
+        self.form = state.form;
+        self.care = state.care;
+        self.last_updated = state.last_updated;
+        self.is_alive = state.is_alive;
+        self.status = LifeStatus::from(state.cause_of_death);
+        self.died_at = state.died_at;
+        self.hunger = state.hunger;
+        self.happiness = state.happiness;
+        self.energy = state.energy;
+        self.health = state.health;
+        self.cleanliness = state.cleanliness;
+        self.is_sick = state.is_sick;
+        self.overfeed_count = state.overfeed_count;
+        self.asleep_since = state.asleep_since;
+        self.napped = state.napped;
+        self.last_fed = state.last_fed;
+        self.last_played = state.last_played;
+        self.last_worked = state.last_worked;
+        self.play_streak = state.play_streak;
+    }
+}
+
+/// Why a pet passed away.
 #[contracttype]
-pub enum DataKey {
-    Pet(Address),
-    Coins(Address),
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DeathCause {
+    Starvation,
+    Sadness,
+    Sickness,
+    OldAge,
+}
+
+/// Stages of a pet's life, see `GameConfig::stages`.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum LifeStage {
+    Egg,
+    Baby,
+    Child,
+    Teen,
+    Adult,
+    Elder,
+}
+
+/// What a pet looks like. Each stage has its own forms, which one the pet
+/// grows into depends on how well it was looked after, see
+/// `engine::evolved_form`.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PetForm {
+    Egg,
+    Blob,
+    // Children
+    Sprout,
+    Pup,
+    Grub,
+    // Teens
+    Sparkle,
+    Scamp,
+    Grump,
+    // Adults
+    Guardian,
+    Workhorse,
+    Buddy,
+    Gremlin,
+    // Elders
+    Sage,
+    Veteran,
+    Grouch,
+}
+
+/// Care a pet received since it entered its current stage.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CareMetrics {
+    pub missed_feedings: u32,
+    pub times_worked: u32,
+    // Happiness summed over every period lived, for the average
+    pub happiness_total: u64,
+    pub periods: u32,
+}
+
+impl CareMetrics {
+    pub fn average_happiness(&self) -> Option<u32> {
+        if self.periods == 0 {
+            return None;
+        }
+        Some((self.happiness_total / u64::from(self.periods)) as u32)
+    }
+}
+
+/// A pet growing into a new stage, kept in its evolution history.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Evolution {
+    pub stage: LifeStage,
+    pub form: PetForm,
+    pub evolved_at: u64,
+    // Care received during the stage the pet grew out of
+    pub missed_feedings: u32,
+    pub average_happiness: u32,
+    pub times_worked: u32,
+}
+
+/// A pet that passed away, kept in its owner's graveyard as a memorial.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeathRecord {
+    pub pet_id: u64,
+    pub name: String,
+    pub birthdate: u64,
+    pub died_at: u64,
+    // Seconds lived
+    pub age: u64,
+    pub cause: DeathCause,
+    pub stage: LifeStage,
+    pub form: PetForm,
+    pub stats: PetStats,
+    pub accessories: Vec<Symbol>,
+}
+
+/// Whether a pet is still alive and, once it is not, what killed it.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LifeStatus {
+    Alive,
+    Dead(DeathCause),
+}
+
+impl From<Option<DeathCause>> for LifeStatus {
+    fn from(cause: Option<DeathCause>) -> Self {
+        match cause {
+            Some(cause) => LifeStatus::Dead(cause),
+            None => LifeStatus::Alive,
+        }
+    }
+}
+
+/// Seconds remaining before each action can be performed again.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionCooldowns {
+    pub feed: u64,
+    pub play: u64,
+    pub work: u64,
 }
 
 #[contract]
@@ -33,222 +308,857 @@ pub struct TamagotchiContract;
 
 #[contractimpl]
 impl TamagotchiContract {
-    pub fn create(env: Env, owner: Address, name: String) -> Pet {
-        owner.require_auth();
+    pub fn __constructor(env: Env, admin: Address) {
+        storage::write_admin(&env, &admin);
+        storage::write_schema_version(&env, SCHEMA_VERSION);
+        storage::write_config(&env, &GameConfig::default());
+        for item in Item::starter_catalog(&env) {
+            storage::write_item(&env, &item);
+        }
+    }
+
+    pub fn get_admin(env: Env) -> Address {
+        storage::read_admin(&env)
+    }
+
+    pub fn set_admin(env: Env, new_admin: Address) {
+        let admin = storage::read_admin(&env);
+        admin.require_auth();
+        storage::extend_instance(&env);
+
+        storage::write_admin(&env, &new_admin);
+    }
+
+    // Swap the contract code in place, keeping every stored pet and balance
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        let admin = storage::read_admin(&env);
+        admin.require_auth();
+        storage::extend_instance(&env);
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    // Bring stored data up to the layout expected by the current code.
+    // Meant to be called by the admin right after `upgrade`.
+    pub fn migrate(env: Env) -> Result<u32, Error> {
+        let admin = storage::read_admin(&env);
+        admin.require_auth();
+        storage::extend_instance(&env);
+
+        let version = storage::read_schema_version(&env);
+        if version > SCHEMA_VERSION {
+            return Err(Error::UnsupportedSchemaVersion);
+        }
+
+        // Deployments from before the shop get the starter items they lack
+        for item in Item::starter_catalog(&env) {
+            if storage::read_item(&env, &item.id).is_none() {
+                storage::write_item(&env, &item);
+            }
+        }
+
+        storage::write_schema_version(&env, SCHEMA_VERSION);
+        Ok(SCHEMA_VERSION)
+    }
+
+    pub fn schema_version(env: Env) -> u32 {
+        storage::read_schema_version(&env)
+    }
+
+    pub fn get_config(env: Env) -> GameConfig {
+        storage::read_config(&env)
+    }
+
+    pub fn set_config(env: Env, config: GameConfig) -> Result<(), Error> {
+        let admin = storage::read_admin(&env);
+        admin.require_auth();
+        storage::extend_instance(&env);
+
+        if !config.is_valid() {
+            return Err(Error::InvalidConfig);
+        }
+        storage::write_config(&env, &config);
+        Ok(())
+    }
+
+    // Add an item to the shop catalog, or replace the one with the same ID
+    pub fn set_item(env: Env, item: Item) -> Result<(), Error> {
+        let admin = storage::read_admin(&env);
+        admin.require_auth();
+        storage::extend_instance(&env);
+
+        if !item.is_valid() {
+            return Err(Error::InvalidItem);
+        }
+        storage::write_item(&env, &item);
+        Ok(())
+    }
 
-        let pet_key = DataKey::Pet(owner.clone());
+    pub fn get_item(env: Env, item_id: Symbol) -> Result<Item, Error> {
+        storage::read_item(&env, &item_id).ok_or(Error::UnknownItem)
+    }
 
-        // Check if pet already exists and update its status with decay logic
-        if env.storage().instance().has(&pet_key) {
-            // Call get_pet to force the decay logic to run and update storage
-            let updated_pet = Self::get_pet(env.clone(), owner.clone());
+    // The shop catalog in the order items were added. Returns at most `limit`
+    // items (capped at 50) starting from the `start`th one.
+    pub fn list_items(env: Env, start: u32, limit: u32) -> Vec<Item> {
+        storage::extend_instance(&env);
+        let item_ids = storage::read_item_ids(&env);
+        let end = start
+            .saturating_add(limit.min(MAX_ITEMS_PAGE))
+            .min(item_ids.len());
 
-            // Check the updated status after decay
-            if updated_pet.is_alive {
-                panic!("Pet already exists for this owner");
+        let mut items = Vec::new(&env);
+        for index in start..end {
+            if let Some(item) = storage::read_item(&env, &item_ids.get_unchecked(index)) {
+                items.push_back(item);
             }
         }
+        items
+    }
+
+    // Sell coins for `price` units of a Stellar asset each, a price of 0 stops
+    // selling them for that asset
+    pub fn set_coin_price(env: Env, asset: Address, price: i128) -> Result<(), Error> {
+        let admin = storage::read_admin(&env);
+        admin.require_auth();
+        storage::extend_instance(&env);
+
+        if price < 0 {
+            return Err(Error::InvalidConfig);
+        }
+        if price == 0 {
+            storage::remove_coin_price(&env, &asset);
+        } else {
+            storage::write_coin_price(&env, &asset, price);
+        }
+        Ok(())
+    }
+
+    pub fn coin_price(env: Env, asset: Address) -> Option<i128> {
+        storage::read_coin_price(&env, &asset)
+    }
+
+    // Move assets paid for coins out of the treasury
+    pub fn withdraw_treasury(
+        env: Env,
+        asset: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        let admin = storage::read_admin(&env);
+        admin.require_auth();
+        storage::extend_instance(&env);
+
+        if amount < 0 {
+            return Err(Error::NegativeAmount);
+        }
+        TokenClient::new(&env, &asset).transfer(&env.current_contract_address(), &to, &amount);
+
+        TreasuryWithdrawn { asset, to, amount }.publish(&env);
+        Ok(())
+    }
+
+    pub fn create(env: Env, owner: Address, name: String) -> Result<Pet, Error> {
+        owner.require_auth();
+        storage::extend_instance(&env);
+
+        let config = storage::read_config(&env);
+
+        // Dead pets keep their slot until they are removed
+        let mut pet_ids = storage::read_owner_pets(&env, &owner);
+        if pet_ids.len() >= config.max_pets_per_owner {
+            return Err(Error::TooManyPets);
+        }
 
         let current_time = env.ledger().timestamp();
+        let id = storage::next_pet_id(&env);
+        let state = PetState::new(&config, current_time);
 
         let pet = Pet {
+            id,
             owner: owner.clone(),
             name: name.clone(),
-            birthdate: current_time,
-            last_updated: current_time,
-            is_alive: true,
-            hunger: MAX_STAT,
-            happiness: MAX_STAT,
-            energy: MAX_STAT,
+            birthdate: state.born_at,
+            last_updated: state.last_updated,
+            is_alive: state.is_alive,
+            status: LifeStatus::from(state.cause_of_death),
+            died_at: state.died_at,
+            revivals: 0,
+            stage: state.stage,
+            form: state.form,
+            care: state.care,
+            hunger: state.hunger,
+            happiness: state.happiness,
+            energy: state.energy,
+            health: state.health,
+            cleanliness: state.cleanliness,
+            is_sick: state.is_sick,
+            overfeed_count: state.overfeed_count,
+            asleep_since: state.asleep_since,
+            napped: state.napped,
+            last_fed: state.last_fed,
+            last_played: state.last_played,
+            last_worked: state.last_worked,
+            play_streak: state.play_streak,
+            equipped: Map::new(&env),
             has_glasses: false,
         };
 
-        // This will overwrite the old pet data if it exists
-        env.storage()
-            .instance()
-            .set(&DataKey::Pet(owner.clone()), &pet);
+        storage::write_pet(&env, &pet);
+        pet_ids.push_back(id);
+        storage::write_owner_pets(&env, &owner, &pet_ids);
 
-        // Initialize coins for new pet (reset to 0 if recreating)
-        env.storage().instance().set(&DataKey::Coins(owner), &0i128);
+        PetCreated {
+            owner,
+            pet_id: id,
+            name,
+            birthdate: current_time,
+        }
+        .publish(&env);
 
-        pet
+        Ok(pet)
     }
 
-    pub fn feed(env: Env, owner: Address) {
+    // Feed the pet, with food from the inventory instead of the free meal
+    // when `item` is given
+    pub fn feed(env: Env, owner: Address, pet_id: u64, item: Option<Symbol>) -> Result<(), Error> {
         owner.require_auth();
-        let mut pet = Self::get_pet(env.clone(), owner.clone());
-        if !pet.is_alive {
-            panic!("Your pet is no longer with us.");
-        }
+        Self::perform_action_with(&env, &owner, pet_id, Action::Feed, item)?;
+        Ok(())
+    }
+
+    // Play with the pet, with a toy from the inventory when `item` is given
+    pub fn play(env: Env, owner: Address, pet_id: u64, item: Option<Symbol>) -> Result<(), Error> {
+        owner.require_auth();
+        Self::perform_action_with(&env, &owner, pet_id, Action::Play, item)?;
+        Ok(())
+    }
 
-        // Apply action
-        pet.hunger = (pet.hunger + 30).min(MAX_STAT);
+    // Use up a food, toy or medicine from the inventory on the pet. It counts
+    // as feeding, playing or curing the pet, cooldowns included.
+    pub fn use_item(env: Env, owner: Address, pet_id: u64, item_id: Symbol) -> Result<(), Error> {
+        owner.require_auth();
+        let item = storage::read_item(&env, &item_id).ok_or(Error::UnknownItem)?;
+        let action = match item.category {
+            ItemCategory::Food => Action::Feed,
+            ItemCategory::Toy => Action::Play,
+            ItemCategory::Medicine => Action::Medicine,
+            ItemCategory::Accessory => return Err(Error::UnsuitableItem),
+        };
+        Self::perform_action_with(&env, &owner, pet_id, action, Some(item_id))?;
+        Ok(())
+    }
 
-        // Update state
-        pet.last_updated = env.ledger().timestamp();
-        env.storage().instance().set(&DataKey::Pet(owner), &pet);
+    pub fn sleep(env: Env, owner: Address, pet_id: u64) -> Result<(), Error> {
+        owner.require_auth();
+        Self::perform_action(&env, &owner, pet_id, Action::Sleep)?;
+        Ok(())
     }
 
-    pub fn play(env: Env, owner: Address) {
+    pub fn wake(env: Env, owner: Address, pet_id: u64) -> Result<(), Error> {
         owner.require_auth();
-        let mut pet = Self::get_pet(env.clone(), owner.clone());
-        if !pet.is_alive {
-            panic!("Your pet is no longer with us.");
+        Self::perform_action(&env, &owner, pet_id, Action::Wake)?;
+        Ok(())
+    }
+
+    pub fn work(env: Env, owner: Address, pet_id: u64) -> Result<(), Error> {
+        owner.require_auth();
+        let config = Self::perform_action(&env, &owner, pet_id, Action::Work)?;
+
+        let coins = token::mint(&env, &owner, config.work_reward)?;
+
+        CoinsEarned {
+            owner,
+            amount: config.work_reward,
+            balance: coins,
         }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn clean(env: Env, owner: Address, pet_id: u64) -> Result<(), Error> {
+        owner.require_auth();
+        Self::perform_action(&env, &owner, pet_id, Action::Clean)?;
+        Ok(())
+    }
 
-        // Apply action
-        pet.happiness = (pet.happiness + 20).min(MAX_STAT);
-        pet.energy = pet.energy.saturating_sub(15);
+    pub fn give_medicine(env: Env, owner: Address, pet_id: u64) -> Result<(), Error> {
+        owner.require_auth();
+        let config = Self::perform_action(&env, &owner, pet_id, Action::Medicine)?;
+
+        // A failed payment rolls the cure back along with the rest of the call
+        let coins = token::burn(&env, &owner, config.medicine_price)?;
 
-        // Update state
-        pet.last_updated = env.ledger().timestamp();
-        env.storage().instance().set(&DataKey::Pet(owner), &pet);
+        CoinsSpent {
+            owner,
+            amount: config.medicine_price,
+            balance: coins,
+        }
+        .publish(&env);
+        Ok(())
     }
 
-    pub fn sleep(env: Env, owner: Address) {
+    // Bring a dead pet back within the revival window, for a price
+    pub fn revive(env: Env, owner: Address, pet_id: u64) -> Result<(), Error> {
         owner.require_auth();
-        let mut pet = Self::get_pet(env.clone(), owner.clone());
-        if !pet.is_alive {
-            panic!("Your pet is no longer with us.");
+        let mut pet = Self::get_pet(env.clone(), pet_id)?;
+        if pet.owner != owner {
+            return Err(Error::NotOwner);
         }
 
-        // Apply action
-        pet.energy = (pet.energy + 40).min(MAX_STAT);
+        let config = storage::read_config(&env);
+        let mut state = pet.state();
+        engine::revive(&config, &mut state, env.ledger().timestamp())?;
+        pet.set_state(&state);
+        pet.revivals += 1;
 
-        // Update state
-        pet.last_updated = env.ledger().timestamp();
-        env.storage().instance().set(&DataKey::Pet(owner), &pet);
+        let coins = token::burn(&env, &owner, config.revive_price)?;
+        storage::write_pet(&env, &pet);
+
+        // The pet is back, so it leaves the graveyard. Its grave is among the
+        // newest, it was dug at most a revival window ago.
+        let graves = storage::read_grave_count(&env, &owner);
+        let grave = (0..graves).rev().find(|&index| {
+            storage::read_grave(&env, &owner, index).is_some_and(|record| record.pet_id == pet_id)
+        });
+        if let Some(index) = grave {
+            storage::remove_grave(&env, &owner, index);
+        }
+
+        CoinsSpent {
+            owner: owner.clone(),
+            amount: config.revive_price,
+            balance: coins,
+        }
+        .publish(&env);
+        PetRevived {
+            owner,
+            pet_id,
+            revivals: pet.revivals,
+            stats: pet.stats(),
+        }
+        .publish(&env);
+        Ok(())
     }
 
-    pub fn work(env: Env, owner: Address) {
+    // Buy items from the shop into the owner's inventory
+    pub fn buy_item(env: Env, owner: Address, item_id: Symbol, quantity: u32) -> Result<(), Error> {
         owner.require_auth();
-        let mut pet = Self::get_pet(env.clone(), owner.clone());
-        if !pet.is_alive {
-            panic!("Your pet is no longer with us.");
+        storage::extend_instance(&env);
+
+        let (_, cost) = Self::sell_item(&env, &owner, &item_id, quantity)?;
+        Self::add_to_inventory(&env, &owner, &item_id, quantity)?;
+
+        ItemBought {
+            owner,
+            item: item_id,
+            quantity,
+            cost,
         }
+        .publish(&env);
+        Ok(())
+    }
 
-        if pet.energy < 20 {
-            panic!("Not enough energy to work.");
+    pub fn get_inventory(env: Env, owner: Address) -> Map<Symbol, u32> {
+        storage::extend_instance(&env);
+        storage::read_inventory(&env, &owner)
+    }
+
+    // Put an accessory from the owner's inventory on the pet, whatever it
+    // wore in that slot goes back to the inventory
+    pub fn equip_item(env: Env, owner: Address, pet_id: u64, item_id: Symbol) -> Result<(), Error> {
+        owner.require_auth();
+        let mut pet = Self::get_live_pet(&env, &owner, pet_id)?;
+        let item = storage::read_item(&env, &item_id).ok_or(Error::UnknownItem)?;
+
+        Self::take_from_inventory(&env, &owner, &item_id)?;
+        Self::dress(&env, &mut pet, &item)?;
+        storage::write_pet(&env, &pet);
+
+        ItemEquipped {
+            owner,
+            pet_id,
+            item: item_id,
+            slot: item.slot,
         }
+        .publish(&env);
+        Ok(())
+    }
 
-        // Apply action
-        pet.energy = pet.energy.saturating_sub(20);
-        pet.happiness = pet.happiness.saturating_sub(10);
+    pub fn unequip_item(
+        env: Env,
+        owner: Address,
+        pet_id: u64,
+        slot: CosmeticSlot,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+        let mut pet = Self::get_live_pet(&env, &owner, pet_id)?;
 
-        // Grant coins
-        let mut coins: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Coins(owner.clone()))
-            .unwrap_or(0);
-        coins += 25;
+        let item_id = pet.take_off(slot).ok_or(Error::SlotEmpty)?;
+        Self::add_to_inventory(&env, &owner, &item_id, 1)?;
+        storage::write_pet(&env, &pet);
 
-        // Update state
-        pet.last_updated = env.ledger().timestamp();
-        env.storage()
-            .instance()
-            .set(&DataKey::Pet(owner.clone()), &pet);
-        env.storage().instance().set(&DataKey::Coins(owner), &coins);
+        ItemUnequipped {
+            owner,
+            pet_id,
+            item: item_id,
+            slot,
+        }
+        .publish(&env);
+        Ok(())
     }
 
-    pub fn mint_glasses(env: Env, owner: Address) {
+    // Kept from before the shop: buys glasses and puts them on in one go
+    pub fn mint_glasses(env: Env, owner: Address, pet_id: u64) -> Result<(), Error> {
         owner.require_auth();
-        let mut pet = Self::get_pet(env.clone(), owner.clone());
-        if !pet.is_alive {
-            panic!("Your pet is no longer with us.");
+        let mut pet = Self::get_live_pet(&env, &owner, pet_id)?;
+
+        let (item, cost) = Self::sell_item(&env, &owner, &GLASSES, 1)?;
+        Self::dress(&env, &mut pet, &item)?;
+        storage::write_pet(&env, &pet);
+
+        AccessoryMinted {
+            owner,
+            pet_id,
+            accessory: GLASSES,
+            price: cost,
         }
+        .publish(&env);
+        Ok(())
+    }
 
-        let mut coins: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Coins(owner.clone()))
-            .unwrap_or(0);
-        if coins < 50 {
-            panic!("Not enough coins to mint glasses.");
+    // Buy `amount` coins with a Stellar asset the admin has priced, the
+    // payment goes to the contract's treasury
+    pub fn purchase_coins(
+        env: Env,
+        buyer: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        buyer.require_auth();
+        storage::extend_instance(&env);
+        if amount < 0 {
+            return Err(Error::NegativeAmount);
         }
-        coins -= 50;
 
-        pet.has_glasses = true;
+        let price = storage::read_coin_price(&env, &asset).ok_or(Error::AssetNotAccepted)?;
+        let cost = amount.checked_mul(price).ok_or(Error::BalanceOverflow)?;
+        TokenClient::new(&env, &asset).transfer(&buyer, env.current_contract_address(), &cost);
+        let balance = token::mint(&env, &buyer, amount)?;
 
-        pet.last_updated = env.ledger().timestamp();
-        env.storage()
-            .instance()
-            .set(&DataKey::Pet(owner.clone()), &pet);
-        env.storage().instance().set(&DataKey::Coins(owner), &coins);
+        CoinsPurchased {
+            buyer,
+            asset,
+            amount,
+            cost,
+            balance,
+        }
+        .publish(&env);
+        Ok(())
     }
 
-    pub fn get_pet(env: Env, owner: Address) -> Pet {
-        let mut pet: Pet = env
-            .storage()
-            .instance()
-            .get(&DataKey::Pet(owner.clone()))
-            .expect("Pet not found");
+    // Send coins to another player, the game's counterpart to the token's
+    // `transfer` that reports failures as errors instead of panics
+    pub fn transfer_coins(env: Env, from: Address, to: Address, amount: i128) -> Result<(), Error> {
+        from.require_auth();
+        storage::extend_instance(&env);
+        if amount < 0 {
+            return Err(Error::NegativeAmount);
+        }
+        if from == to {
+            return Err(Error::SelfTransfer);
+        }
+
+        token::move_coins(&env, &from, &to, amount)?;
 
+        Transfer { from, to, amount }.publish(&env);
+        Ok(())
+    }
+
+    // Buy an item for someone else's pet, paid from the giver's coins.
+    // Accessories are put on the pet, anything else goes to its owner's
+    // inventory.
+    pub fn gift_item(env: Env, from: Address, pet_id: u64, item: Symbol) -> Result<(), Error> {
+        from.require_auth();
+        let mut pet = Self::get_pet(env.clone(), pet_id)?;
+        if pet.owner == from {
+            return Err(Error::SelfTransfer);
+        }
         if !pet.is_alive {
-            return pet;
+            return Err(Error::PetDead);
+        }
+        let catalog_item = storage::read_item(&env, &item).ok_or(Error::UnknownItem)?;
+        if pet.equipped.get(catalog_item.slot) == Some(item.clone()) {
+            return Err(Error::ItemAlreadyOwned);
         }
 
-        let current_time = env.ledger().timestamp();
-        let time_elapsed = current_time.saturating_sub(pet.last_updated);
+        let (catalog_item, cost) = Self::sell_item(&env, &from, &item, 1)?;
+        if catalog_item.slot == CosmeticSlot::None {
+            Self::add_to_inventory(&env, &pet.owner, &item, 1)?;
+        } else {
+            Self::dress(&env, &mut pet, &catalog_item)?;
+            storage::write_pet(&env, &pet);
+        }
+
+        ItemGifted {
+            from,
+            owner: pet.owner,
+            pet_id,
+            item,
+            price: cost,
+        }
+        .publish(&env);
+        Ok(())
+    }
 
-        // Stats decay every hour (3600 seconds) for 7-day lifespan
-        let decay_periods = time_elapsed / 3600;
+    pub fn get_pet(env: Env, pet_id: u64) -> Result<Pet, Error> {
+        storage::extend_instance(&env);
+        let stored = storage::read_pet(&env, pet_id).ok_or(Error::PetNotFound)?;
 
-        if decay_periods > 0 {
-            // Hunger decays by 1 point per hour (dies in ~4 days if not fed)
-            pet.hunger = pet.hunger.saturating_sub(decay_periods as u32);
-            // Happiness decays by 1 point per 2 hours (dies in ~8 days if not played with)
-            pet.happiness = pet.happiness.saturating_sub((decay_periods as u32) / 2);
+        let config = storage::read_config(&env);
+        let mut pet = stored.clone();
+        let mut state = pet.state();
+        let mut evolutions = Vec::new(&env);
+        let cause =
+            engine::decay_with(&config, &mut state, env.ledger().timestamp(), |evolution| {
+                evolutions.push_back(evolution)
+            });
+        pet.set_state(&state);
 
-            pet.last_updated = current_time;
+        // Persist only when decay actually changed something
+        if pet != stored {
+            storage::write_pet(&env, &pet);
+        }
 
-            // Check if pet should die
-            if pet.hunger == 0 || pet.happiness == 0 {
-                pet.is_alive = false;
+        if !evolutions.is_empty() {
+            let mut history = storage::read_evolutions(&env, pet_id);
+            history.append(&evolutions);
+            storage::write_evolutions(&env, pet_id, &history);
+            for evolution in evolutions {
+                PetEvolved {
+                    owner: pet.owner.clone(),
+                    pet_id,
+                    evolution,
+                }
+                .publish(&env);
             }
+        }
 
-            // Always update storage after any changes
-            env.storage().instance().set(&DataKey::Pet(owner), &pet);
+        if let Some(cause) = cause {
+            // Bury the pet right away, the record outlives the pet itself
+            let died_at = pet.died_at.unwrap_or(pet.last_updated);
+            let record = DeathRecord {
+                pet_id,
+                name: pet.name.clone(),
+                birthdate: pet.birthdate,
+                died_at,
+                age: died_at.saturating_sub(pet.birthdate),
+                cause,
+                stage: pet.stage,
+                form: pet.form,
+                stats: pet.stats(),
+                accessories: pet.accessories(),
+            };
+            storage::add_grave(&env, &pet.owner, &record);
+
+            PetDied {
+                owner: pet.owner.clone(),
+                pet_id,
+                cause,
+                died_at,
+                stats: record.stats,
+            }
+            .publish(&env);
         }
 
-        pet
+        Ok(pet)
     }
 
-    pub fn get_coins(env: Env, owner: Address) -> i128 {
-        env.storage()
-            .instance()
-            .get(&DataKey::Coins(owner))
-            .unwrap_or(0)
+    // Same as `get_pet` but never writes: the decayed state is computed in
+    // memory only, and neither the pet nor its TTL is touched.
+    pub fn view_pet(env: Env, pet_id: u64) -> Result<Pet, Error> {
+        let mut pet = storage::peek_pet(&env, pet_id).ok_or(Error::PetNotFound)?;
+        let config = storage::read_config(&env);
+        let mut state = pet.state();
+        engine::decay(&config, &mut state, env.ledger().timestamp());
+        pet.set_state(&state);
+        Ok(pet)
+    }
+
+    // Every stage the pet has grown into so far, oldest first. Read-only like
+    // `view_pet`, evolutions still pending in the decay are included.
+    pub fn evolution_history(env: Env, pet_id: u64) -> Result<Vec<Evolution>, Error> {
+        let pet = storage::peek_pet(&env, pet_id).ok_or(Error::PetNotFound)?;
+        let config = storage::read_config(&env);
+        let mut history = storage::peek_evolutions(&env, pet_id);
+        let mut state = pet.state();
+        engine::decay_with(&config, &mut state, env.ledger().timestamp(), |evolution| {
+            history.push_back(evolution)
+        });
+        Ok(history)
+    }
+
+    pub fn cooldowns(env: Env, pet_id: u64) -> Result<ActionCooldowns, Error> {
+        let pet = storage::peek_pet(&env, pet_id).ok_or(Error::PetNotFound)?;
+        let config = storage::read_config(&env);
+        let state = pet.state();
+        let now = env.ledger().timestamp();
+        Ok(ActionCooldowns {
+            feed: engine::cooldown_remaining(&config, &state, Action::Feed, now),
+            play: engine::cooldown_remaining(&config, &state, Action::Play, now),
+            work: engine::cooldown_remaining(&config, &state, Action::Work, now),
+        })
     }
 
-    // Check pet status and force cleanup
-    pub fn debug_pet_status(env: Env, owner: Address) -> (bool, bool, u32, u32) {
-        let pet_exists = env.storage().instance().has(&DataKey::Pet(owner.clone()));
+    // Pets the owner has lost, in the order their deaths were noticed: a pet
+    // is buried the first time it is loaded after dying, so one that died
+    // earlier can come after one that died later (`died_at` tells when each
+    // really did), and a dead pet nobody has looked at since is not listed
+    // yet. Returns at most `limit` graves (capped at 50) starting from the
+    // `start`th one.
+    pub fn get_graveyard(env: Env, owner: Address, start: u32, limit: u32) -> Vec<DeathRecord> {
+        storage::extend_instance(&env);
+        let count = storage::read_grave_count(&env, &owner);
+        let end = start
+            .saturating_add(limit.min(MAX_GRAVEYARD_PAGE))
+            .min(count);
 
-        if !pet_exists {
-            return (false, false, 0, 0);
+        let mut graves = Vec::new(&env);
+        for index in start..end {
+            if let Some(record) = storage::read_grave(&env, &owner, index) {
+                graves.push_back(record);
+            }
         }
+        graves
+    }
 
-        let pet = Self::get_pet(env.clone(), owner.clone());
-        (pet_exists, pet.is_alive, pet.hunger, pet.happiness)
+    // Same as the token's `balance`, kept for existing clients
+    pub fn get_coins(env: Env, owner: Address) -> i128 {
+        storage::extend_instance(&env);
+        storage::read_coins(&env, &owner)
     }
 
-    // Method to remove any pet (alive or dead)
-    pub fn remove_pet(env: Env, owner: Address) {
+    pub fn list_pets(env: Env, owner: Address) -> Vec<u64> {
+        storage::extend_instance(&env);
+        storage::read_owner_pets(&env, &owner)
+    }
+
+    // Check pet status without persisting decay
+    pub fn debug_pet_status(env: Env, pet_id: u64) -> (bool, bool, u32, u32) {
+        match Self::view_pet(env, pet_id) {
+            Ok(pet) => (true, pet.is_alive, pet.hunger, pet.happiness),
+            Err(_) => (false, false, 0, 0),
+        }
+    }
+
+    // Method to remove any pet (alive or dead), the owner keeps their coins
+    // and gets back the accessories a living pet was wearing. Decay is
+    // applied first so a pet that died unnoticed still gets its grave.
+    pub fn remove_pet(env: Env, owner: Address, pet_id: u64) -> Result<(), Error> {
         owner.require_auth();
+        let pet = Self::get_pet(env.clone(), pet_id)?;
+        if pet.owner != owner {
+            return Err(Error::NotOwner);
+        }
+
+        // Dead pets are buried with theirs
+        if pet.is_alive {
+            for item_id in pet.accessories() {
+                Self::add_to_inventory(&env, &owner, &item_id, 1)?;
+            }
+        }
+
+        storage::remove_pet(&env, pet_id);
+        storage::remove_evolutions(&env, pet_id);
+        let mut pet_ids = storage::read_owner_pets(&env, &owner);
+        if let Some(index) = pet_ids.first_index_of(pet_id) {
+            pet_ids.remove(index);
+        }
+        storage::write_owner_pets(&env, &owner, &pet_ids);
+
+        PetRemoved {
+            owner,
+            pet_id,
+            was_alive: pet.is_alive,
+        }
+        .publish(&env);
+        Ok(())
+    }
+}
+
+impl TamagotchiContract {
+    // Charge `buyer` for `quantity` units of a catalog item and take them out
+    // of stock. Returns the item and what it cost.
+    fn sell_item(
+        env: &Env,
+        buyer: &Address,
+        item_id: &Symbol,
+        quantity: u32,
+    ) -> Result<(Item, i128), Error> {
+        if quantity == 0 {
+            return Err(Error::InvalidQuantity);
+        }
+        let mut item = storage::read_item(env, item_id).ok_or(Error::UnknownItem)?;
+        if let Some(stock) = item.stock {
+            if stock < quantity {
+                return Err(Error::OutOfStock);
+            }
+            item.stock = Some(stock - quantity);
+            storage::write_item(env, &item);
+        }
+
+        let cost = item
+            .price
+            .checked_mul(quantity.into())
+            .ok_or(Error::BalanceOverflow)?;
+        let coins = token::burn(env, buyer, cost)?;
 
-        let pet_key = DataKey::Pet(owner.clone());
-        let coins_key = DataKey::Coins(owner.clone());
+        CoinsSpent {
+            owner: buyer.clone(),
+            amount: cost,
+            balance: coins,
+        }
+        .publish(env);
+        Ok((item, cost))
+    }
+
+    fn add_to_inventory(
+        env: &Env,
+        owner: &Address,
+        item_id: &Symbol,
+        quantity: u32,
+    ) -> Result<(), Error> {
+        let mut inventory = storage::read_inventory(env, owner);
+        let count = inventory
+            .get(item_id.clone())
+            .unwrap_or(0)
+            .checked_add(quantity)
+            .ok_or(Error::InvalidQuantity)?;
+        inventory.set(item_id.clone(), count);
+        storage::write_inventory(env, owner, &inventory);
+        Ok(())
+    }
 
-        // Remove pet data if it exists
-        if env.storage().instance().has(&pet_key) {
-            env.storage().instance().remove(&pet_key);
+    fn take_from_inventory(env: &Env, owner: &Address, item_id: &Symbol) -> Result<(), Error> {
+        let mut inventory = storage::read_inventory(env, owner);
+        match inventory.get(item_id.clone()).unwrap_or(0) {
+            0 => return Err(Error::ItemNotOwned),
+            1 => {
+                inventory.remove(item_id.clone());
+            }
+            count => inventory.set(item_id.clone(), count - 1),
         }
+        storage::write_inventory(env, owner, &inventory);
+        Ok(())
+    }
 
-        // Remove coins data if it exists
-        if env.storage().instance().has(&coins_key) {
-            env.storage().instance().remove(&coins_key);
+    // Put an accessory on the pet, what it wore in that slot goes back to the
+    // owner's inventory. The caller persists the pet.
+    fn dress(env: &Env, pet: &mut Pet, item: &Item) -> Result<(), Error> {
+        if item.slot == CosmeticSlot::None {
+            return Err(Error::NotEquippable);
+        }
+        if let Some(previous) = pet.wear(item.slot, item.id.clone()) {
+            Self::add_to_inventory(env, &pet.owner, &previous, 1)?;
         }
+        Ok(())
+    }
+
+    // Load the caller's pet with decay applied, failing if it has already passed away
+    fn get_live_pet(env: &Env, owner: &Address, pet_id: u64) -> Result<Pet, Error> {
+        let pet = Self::get_pet(env.clone(), pet_id)?;
+        if pet.owner != *owner {
+            return Err(Error::NotOwner);
+        }
+        if !pet.is_alive {
+            return Err(Error::PetDead);
+        }
+        Ok(pet)
+    }
+
+    // Run a care action through the game rules, persist the pet and report
+    // the change. Returns the config the action was evaluated with.
+    fn perform_action(
+        env: &Env,
+        owner: &Address,
+        pet_id: u64,
+        action: Action,
+    ) -> Result<GameConfig, Error> {
+        Self::perform_action_with(env, owner, pet_id, action, None)
+    }
+
+    // Same as `perform_action`, using up one of the owner's items for it
+    // when `item` is given
+    fn perform_action_with(
+        env: &Env,
+        owner: &Address,
+        pet_id: u64,
+        action: Action,
+        item: Option<Symbol>,
+    ) -> Result<GameConfig, Error> {
+        let mut pet = Self::get_live_pet(env, owner, pet_id)?;
+        let config = storage::read_config(env);
+        let before = pet.stats();
+
+        let effects = match &item {
+            Some(item_id) => {
+                let item = storage::read_item(env, item_id).ok_or(Error::UnknownItem)?;
+                let suitable = match action {
+                    Action::Feed => item.category == ItemCategory::Food,
+                    Action::Play => item.category == ItemCategory::Toy,
+                    Action::Medicine => item.category == ItemCategory::Medicine,
+                    _ => false,
+                };
+                if !suitable {
+                    return Err(Error::UnsuitableItem);
+                }
+                Self::take_from_inventory(env, owner, item_id)?;
+                Some(item.effects)
+            }
+            None => None,
+        };
+
+        let mut state = pet.state();
+        engine::perform_with(
+            &config,
+            &mut state,
+            action,
+            env.ledger().timestamp(),
+            effects,
+        )?;
+        pet.set_state(&state);
+        storage::write_pet(env, &pet);
+
+        ActionPerformed {
+            owner: owner.clone(),
+            pet_id,
+            action: action_symbol(action),
+            before,
+            after: pet.stats(),
+        }
+        .publish(env);
+        if let Some(item) = item {
+            ItemUsed {
+                owner: owner.clone(),
+                pet_id,
+                item,
+                action: action_symbol(action),
+            }
+            .publish(env);
+        }
+        Ok(config)
+    }
+}
+
+fn action_symbol(action: Action) -> Symbol {
+    match action {
+        Action::Feed => symbol_short!("feed"),
+        Action::Play => symbol_short!("play"),
+        Action::Sleep => symbol_short!("sleep"),
+        Action::Wake => symbol_short!("wake"),
+        Action::Work => symbol_short!("work"),
+        Action::Medicine => symbol_short!("medicine"),
+        Action::Clean => symbol_short!("clean"),
     }
 }
 
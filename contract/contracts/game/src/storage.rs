@@ -0,0 +1,365 @@
+use soroban_sdk::{contracttype, Address, Env, Map, Symbol, Vec};
+
+use crate::{DeathRecord, Evolution, GameConfig, Item, Pet};
+
+// Ledgers close roughly every 5 seconds
+pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
+
+// The instance entry only holds contract-wide data, keep it alive for a week
+pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// Per-player entries live for a month after the player last touched them
+pub(crate) const PLAYER_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const PLAYER_LIFETIME_THRESHOLD: u32 = PLAYER_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+#[contracttype]
+pub enum DataKey {
+    // Contract-wide entries, kept in instance storage
+    Admin,
+    SchemaVersion,
+    Config,
+    NextPetId,
+    // Units of a Stellar asset charged per coin bought with it
+    CoinPrice(Address),
+
+    // The shop catalog, kept in persistent storage since the admin can grow
+    // it without bound. `ItemIds` lists the items in the order they were
+    // added.
+    ItemIds,
+    Item(Symbol),
+
+    // Per-player entries, kept in persistent storage
+    Pet(u64),
+    OwnerPets(Address),
+    Coins(Address),
+    // Items an owner bought and hasn't put on a pet or used yet
+    Inventory(Address),
+    Evolutions(u64),
+    // Number of pets an owner has buried, each kept in its own `Grave` entry
+    // so the graveyard can grow without bound
+    GraveCount(Address),
+    Grave(Address, u32),
+
+    // Coin allowances from an owner to a spender, kept in temporary storage
+    // until they expire
+    Allowance(Address, Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+pub fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn read_admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("Contract has no admin")
+}
+
+pub fn write_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&DataKey::Admin, admin);
+}
+
+pub fn read_schema_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::SchemaVersion)
+        .unwrap_or(0)
+}
+
+pub fn write_schema_version(env: &Env, version: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::SchemaVersion, &version);
+}
+
+// Deployments that predate the stored config fall back to the defaults
+pub fn read_config(env: &Env) -> GameConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .unwrap_or_default()
+}
+
+pub fn write_config(env: &Env, config: &GameConfig) {
+    env.storage().instance().set(&DataKey::Config, config);
+}
+
+pub fn read_coin_price(env: &Env, asset: &Address) -> Option<i128> {
+    env.storage()
+        .instance()
+        .get(&DataKey::CoinPrice(asset.clone()))
+}
+
+pub fn write_coin_price(env: &Env, asset: &Address, price: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::CoinPrice(asset.clone()), &price);
+}
+
+pub fn remove_coin_price(env: &Env, asset: &Address) {
+    env.storage()
+        .instance()
+        .remove(&DataKey::CoinPrice(asset.clone()));
+}
+
+// Catalog entries are kept alive like player entries, for as long as players
+// keep shopping
+pub fn read_item_ids(env: &Env) -> Vec<Symbol> {
+    let key = DataKey::ItemIds;
+    match env.storage().persistent().get(&key) {
+        Some(item_ids) => {
+            extend_player_entry(env, &key);
+            item_ids
+        }
+        None => Vec::new(env),
+    }
+}
+
+pub fn read_item(env: &Env, item_id: &Symbol) -> Option<Item> {
+    let key = DataKey::Item(item_id.clone());
+    let item = env.storage().persistent().get(&key);
+    if item.is_some() {
+        extend_player_entry(env, &key);
+    }
+    item
+}
+
+// Add the item to the catalog or replace the existing entry with its ID
+pub fn write_item(env: &Env, item: &Item) {
+    let key = DataKey::Item(item.id.clone());
+    if !env.storage().persistent().has(&key) {
+        let mut item_ids = read_item_ids(env);
+        item_ids.push_back(item.id.clone());
+        env.storage().persistent().set(&DataKey::ItemIds, &item_ids);
+        extend_player_entry(env, &DataKey::ItemIds);
+    }
+    env.storage().persistent().set(&key, item);
+    extend_player_entry(env, &key);
+}
+
+fn extend_player_entry(env: &Env, key: &DataKey) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, PLAYER_LIFETIME_THRESHOLD, PLAYER_BUMP_AMOUNT);
+}
+
+// Hand out pet IDs sequentially, starting from 1
+pub fn next_pet_id(env: &Env) -> u64 {
+    let id = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextPetId)
+        .unwrap_or(1u64);
+    env.storage().instance().set(&DataKey::NextPetId, &(id + 1));
+    id
+}
+
+pub fn read_pet(env: &Env, pet_id: u64) -> Option<Pet> {
+    let key = DataKey::Pet(pet_id);
+    let pet = env.storage().persistent().get(&key);
+    if pet.is_some() {
+        extend_player_entry(env, &key);
+    }
+    pet
+}
+
+// Read a pet without bumping its TTL, for read-only views
+pub fn peek_pet(env: &Env, pet_id: u64) -> Option<Pet> {
+    env.storage().persistent().get(&DataKey::Pet(pet_id))
+}
+
+pub fn write_pet(env: &Env, pet: &Pet) {
+    let key = DataKey::Pet(pet.id);
+    env.storage().persistent().set(&key, pet);
+    extend_player_entry(env, &key);
+}
+
+pub fn remove_pet(env: &Env, pet_id: u64) {
+    env.storage().persistent().remove(&DataKey::Pet(pet_id));
+}
+
+pub fn read_evolutions(env: &Env, pet_id: u64) -> Vec<Evolution> {
+    let key = DataKey::Evolutions(pet_id);
+    match env.storage().persistent().get(&key) {
+        Some(evolutions) => {
+            extend_player_entry(env, &key);
+            evolutions
+        }
+        None => Vec::new(env),
+    }
+}
+
+// Read a pet's evolutions without bumping their TTL, for read-only views
+pub fn peek_evolutions(env: &Env, pet_id: u64) -> Vec<Evolution> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Evolutions(pet_id))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+pub fn write_evolutions(env: &Env, pet_id: u64, evolutions: &Vec<Evolution>) {
+    let key = DataKey::Evolutions(pet_id);
+    env.storage().persistent().set(&key, evolutions);
+    extend_player_entry(env, &key);
+}
+
+pub fn remove_evolutions(env: &Env, pet_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Evolutions(pet_id));
+}
+
+pub fn read_owner_pets(env: &Env, owner: &Address) -> Vec<u64> {
+    let key = DataKey::OwnerPets(owner.clone());
+    match env.storage().persistent().get(&key) {
+        Some(pet_ids) => {
+            extend_player_entry(env, &key);
+            pet_ids
+        }
+        None => Vec::new(env),
+    }
+}
+
+pub fn write_owner_pets(env: &Env, owner: &Address, pet_ids: &Vec<u64>) {
+    let key = DataKey::OwnerPets(owner.clone());
+    if pet_ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, pet_ids);
+        extend_player_entry(env, &key);
+    }
+}
+
+pub fn read_inventory(env: &Env, owner: &Address) -> Map<Symbol, u32> {
+    let key = DataKey::Inventory(owner.clone());
+    match env.storage().persistent().get(&key) {
+        Some(inventory) => {
+            extend_player_entry(env, &key);
+            inventory
+        }
+        None => Map::new(env),
+    }
+}
+
+pub fn write_inventory(env: &Env, owner: &Address, inventory: &Map<Symbol, u32>) {
+    let key = DataKey::Inventory(owner.clone());
+    if inventory.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, inventory);
+        extend_player_entry(env, &key);
+    }
+}
+
+pub fn read_grave_count(env: &Env, owner: &Address) -> u32 {
+    let key = DataKey::GraveCount(owner.clone());
+    match env.storage().persistent().get(&key) {
+        Some(count) => {
+            extend_player_entry(env, &key);
+            count
+        }
+        None => 0,
+    }
+}
+
+pub fn read_grave(env: &Env, owner: &Address, index: u32) -> Option<DeathRecord> {
+    let key = DataKey::Grave(owner.clone(), index);
+    let record = env.storage().persistent().get(&key);
+    if record.is_some() {
+        extend_player_entry(env, &key);
+    }
+    record
+}
+
+// Graves are numbered from 0 in the order deaths are noticed, which is not
+// necessarily the order the pets died in
+pub fn add_grave(env: &Env, owner: &Address, record: &DeathRecord) {
+    let count = read_grave_count(env, owner);
+    let key = DataKey::Grave(owner.clone(), count);
+    env.storage().persistent().set(&key, record);
+    extend_player_entry(env, &key);
+
+    let key = DataKey::GraveCount(owner.clone());
+    env.storage().persistent().set(&key, &(count + 1));
+    extend_player_entry(env, &key);
+}
+
+// Take a grave out of the graveyard, later ones move down to keep the order
+pub fn remove_grave(env: &Env, owner: &Address, index: u32) {
+    let count = read_grave_count(env, owner);
+    if index >= count {
+        return;
+    }
+    for later in index + 1..count {
+        if let Some(record) = read_grave(env, owner, later) {
+            let key = DataKey::Grave(owner.clone(), later - 1);
+            env.storage().persistent().set(&key, &record);
+            extend_player_entry(env, &key);
+        }
+    }
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Grave(owner.clone(), count - 1));
+
+    let key = DataKey::GraveCount(owner.clone());
+    if count == 1 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &(count - 1));
+        extend_player_entry(env, &key);
+    }
+}
+
+pub fn read_coins(env: &Env, owner: &Address) -> i128 {
+    let key = DataKey::Coins(owner.clone());
+    match env.storage().persistent().get(&key) {
+        Some(coins) => {
+            extend_player_entry(env, &key);
+            coins
+        }
+        None => 0,
+    }
+}
+
+pub fn write_coins(env: &Env, owner: &Address, coins: i128) {
+    let key = DataKey::Coins(owner.clone());
+    env.storage().persistent().set(&key, &coins);
+    extend_player_entry(env, &key);
+}
+
+// Expired allowances read as zero
+pub fn read_allowance(env: &Env, from: &Address, spender: &Address) -> AllowanceValue {
+    let key = DataKey::Allowance(from.clone(), spender.clone());
+    match env.storage().temporary().get::<_, AllowanceValue>(&key) {
+        Some(allowance) if allowance.expiration_ledger >= env.ledger().sequence() => allowance,
+        _ => AllowanceValue {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+// The entry lives exactly as long as the allowance it holds
+pub fn write_allowance(env: &Env, from: &Address, spender: &Address, allowance: &AllowanceValue) {
+    let key = DataKey::Allowance(from.clone(), spender.clone());
+    env.storage().temporary().set(&key, allowance);
+    if allowance.amount > 0 {
+        let live_for = allowance
+            .expiration_ledger
+            .saturating_sub(env.ledger().sequence());
+        env.storage()
+            .temporary()
+            .extend_ttl(&key, live_for, live_for);
+    }
+}
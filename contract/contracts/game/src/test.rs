@@ -1,13 +1,42 @@
 #![cfg(test)]
+extern crate std;
 
-use super::{TamagotchiContract, TamagotchiContractClient, MAX_STAT};
+use super::events::{
+    AccessoryMinted, ActionPerformed, Approve, Burn, CoinsEarned, CoinsPurchased, CoinsSpent,
+    ItemBought, ItemEquipped, ItemGifted, ItemUsed, Mint, PetCreated, PetDied, PetEvolved,
+    PetRemoved, PetRevived, PetStats, Transfer, TransferMuxed, TreasuryWithdrawn,
+};
+use super::storage::{self, DAY_IN_LEDGERS, PLAYER_BUMP_AMOUNT, PLAYER_LIFETIME_THRESHOLD};
+use super::{
+    ActionCooldowns, CosmeticSlot, DataKey, DeathCause, DeathRecord, Error, Evolution, GameConfig,
+    Item, ItemCategory, LifeStage, LifeStatus, Pet, PetForm, StatEffects, TamagotchiContract,
+    TamagotchiContractClient, MAX_STAT, SCHEMA_VERSION,
+};
 use soroban_sdk::{
-    testutils::{Address as _, Ledger, LedgerInfo},
-    Address, Env, String,
+    map, symbol_short,
+    testutils::{
+        storage::Persistent as _, Address as _, AuthorizedFunction, AuthorizedInvocation,
+        Events as _, Ledger, LedgerInfo, MockAuth, MockAuthInvoke,
+        MuxedAddress as MuxedAddressTestutils,
+    },
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, BytesN, Env, Event, IntoVal, MuxedAddress, String, Symbol, Val, Vec,
 };
 
+// Pets of this contract hatch as adults and never grow old, the life stage
+// tests opt back into aging with `set_config`
 fn create_tamagotchi_contract(env: &Env) -> TamagotchiContractClient<'_> {
-    TamagotchiContractClient::new(env, &env.register(TamagotchiContract, ()))
+    let admin = Address::generate(env);
+    let contract_id = env.register(TamagotchiContract, (&admin,));
+    env.as_contract(&contract_id, || {
+        storage::write_config(env, &GameConfig::ageless())
+    });
+    TamagotchiContractClient::new(env, &contract_id)
+}
+
+// Let the longest default action cooldown run out before repeating an action
+fn wait_for_cooldowns(env: &Env) {
+    advance_ledger(env, 3600);
 }
 
 fn advance_ledger(env: &Env, seconds: u64) {
@@ -33,29 +62,109 @@ fn test_create_pet() {
 
     let pet = client.create(&owner, &name);
 
+    assert_eq!(pet.id, 1);
     assert_eq!(pet.owner, owner);
     assert_eq!(pet.name, name);
-    assert_eq!(pet.is_alive, true);
+    assert!(pet.is_alive);
     assert_eq!(pet.hunger, MAX_STAT);
     assert_eq!(pet.happiness, MAX_STAT);
     assert_eq!(pet.energy, MAX_STAT);
-    assert_eq!(pet.has_glasses, false);
+    assert!(!pet.has_glasses);
 
     let coins = client.get_coins(&owner);
     assert_eq!(coins, 0);
 }
 
 #[test]
-#[should_panic(expected = "Pet already exists for this owner")]
-fn test_create_pet_already_exists() {
+fn test_create_multiple_pets() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    let name = String::from_str(&env, "Pixel");
 
+    let first = client.create(&owner, &String::from_str(&env, "Pixel"));
+    let second = client.create(&owner, &String::from_str(&env, "Byte"));
+
+    assert_eq!(first.id, 1);
+    assert_eq!(second.id, 2);
+    assert_eq!(client.list_pets(&owner), vec![&env, 1, 2]);
+
+    // Each pet keeps its own stats
+    client.feed(&owner, &first.id, &None);
+    client.work(&owner, &second.id);
+    assert_eq!(client.get_pet(&first.id).energy, MAX_STAT);
+    assert_eq!(client.get_pet(&second.id).energy, 80);
+    assert_eq!(
+        client.get_pet(&second.id).name,
+        String::from_str(&env, "Byte")
+    );
+}
+
+#[test]
+fn test_create_too_many_pets() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let name = String::from_str(&env, "Clone");
+
+    for _ in 0..client.get_config().max_pets_per_owner {
+        client.create(&owner, &name);
+    }
+    assert_eq!(
+        client.try_create(&owner, &name),
+        Err(Ok(Error::TooManyPets))
+    );
+
+    // Removing a pet frees its slot
+    client.remove_pet(&owner, &1);
     client.create(&owner, &name);
-    client.create(&owner, &name); // Should panic here
+}
+
+#[test]
+fn test_action_on_someone_elses_pet() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "Mine")).id;
+
+    assert_eq!(
+        client.try_feed(&stranger, &pet_id, &None),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(
+        client.try_play(&stranger, &pet_id, &None),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(
+        client.try_sleep(&stranger, &pet_id),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(
+        client.try_wake(&stranger, &pet_id),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(
+        client.try_clean(&stranger, &pet_id),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(
+        client.try_work(&stranger, &pet_id),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(
+        client.try_mint_glasses(&stranger, &pet_id),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(
+        client.try_remove_pet(&stranger, &pet_id),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(client.get_coins(&stranger), 0);
+    assert_eq!(client.list_pets(&owner), vec![&env, pet_id]);
 }
 
 #[test]
@@ -65,12 +174,12 @@ fn test_feed() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "Giga"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Giga")).id;
 
     // Let's simulate decay first - 5 hours
     advance_ledger(&env, 3600 * 5);
-    client.feed(&owner);
-    let pet = client.get_pet(&owner);
+    client.feed(&owner, &pet_id, &None);
+    let pet = client.get_pet(&pet_id);
 
     // Initial: 100. Decay over 5 hours: 100 - 5 = 95.
     assert_eq!(pet.hunger, MAX_STAT);
@@ -82,19 +191,19 @@ fn test_play() {
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    client.create(&owner, &String::from_str(&env, "Player"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Player")).id;
 
     advance_ledger(&env, 3600 * 10); // 10 hours
                                      // Initial happiness: 100. Decay: 100 - (10/2) = 95.
-                                     // Initial energy: 100. No decay for energy.
+                                     // Initial energy: 100. Drain: 100 - (10/3) = 97.
 
-    client.play(&owner);
-    let pet = client.get_pet(&owner);
+    client.play(&owner, &pet_id, &None);
+    let pet = client.get_pet(&pet_id);
 
     // Happiness: 95 + 20 = 115, capped at 100.
-    // Energy: 100 - 15 = 85.
+    // Energy: 97 - 15 = 82.
     assert_eq!(pet.happiness, MAX_STAT);
-    assert_eq!(pet.energy, 85);
+    assert_eq!(pet.energy, 82);
 }
 
 #[test]
@@ -103,10 +212,10 @@ fn test_work() {
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    client.create(&owner, &String::from_str(&env, "Worker"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Worker")).id;
 
-    client.work(&owner);
-    let pet = client.get_pet(&owner);
+    client.work(&owner, &pet_id);
+    let pet = client.get_pet(&pet_id);
     let coins = client.get_coins(&owner);
 
     // Energy: 100 - 20 = 80
@@ -118,23 +227,26 @@ fn test_work() {
 }
 
 #[test]
-#[should_panic(expected = "Not enough energy to work.")]
 fn test_work_no_energy() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    client.create(&owner, &String::from_str(&env, "Tired"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Tired")).id;
 
-    // Work 5 times to drain energy
-    for _ in 0..5 {
-        client.work(&owner);
+    // Work 4 times to drain energy
+    for _ in 0..4 {
+        client.work(&owner, &pet_id);
+        wait_for_cooldowns(&env);
     }
-    // Energy should be 0 now.
-    let pet = client.get_pet(&owner);
-    assert_eq!(pet.energy, 0);
+    // 4 shifts and 4 hours awake, which cost 1 more, leave 19 energy
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 19);
 
-    client.work(&owner); // Should panic
+    assert_eq!(
+        client.try_work(&owner, &pet_id),
+        Err(Ok(Error::InsufficientEnergy))
+    );
 }
 
 #[test]
@@ -143,16 +255,46 @@ fn test_sleep() {
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    client.create(&owner, &String::from_str(&env, "Sleepy"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Sleepy")).id;
 
     // Work to reduce energy
-    client.work(&owner); // Energy: 80
-    client.work(&owner); // Energy: 60
-
-    client.sleep(&owner);
-    let pet = client.get_pet(&owner);
-    // Energy: 60 + 40 = 100
+    client.work(&owner, &pet_id); // Energy: 80
+    wait_for_cooldowns(&env);
+    client.work(&owner, &pet_id); // Energy: 60
+
+    // Falling asleep gives no energy right away
+    client.sleep(&owner, &pet_id);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 60);
+    assert_eq!(pet.asleep_since, Some(env.ledger().timestamp()));
+
+    // Energy: 60 + 4 * 10 = 100
+    advance_ledger(&env, 3600 * 4);
+    client.wake(&owner, &pet_id);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.energy, MAX_STAT);
+    assert!(pet.asleep_since.is_none());
+}
+
+#[test]
+fn test_clean() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Messy")).id;
+
+    // Cleanliness drops by 1 every 2 hours
+    advance_ledger(&env, 3600 * 10);
+    assert_eq!(client.get_pet(&pet_id).cleanliness, 95);
+
+    // Digesting a meal makes a mess: -5 for each of the next 2 hours
+    client.feed(&owner, &pet_id, &None);
+    advance_ledger(&env, 3600 * 2);
+    assert_eq!(client.get_pet(&pet_id).cleanliness, 85);
+
+    client.clean(&owner, &pet_id);
+    assert_eq!(client.get_pet(&pet_id).cleanliness, MAX_STAT);
 }
 
 #[test]
@@ -161,35 +303,46 @@ fn test_stat_decay_and_death() {
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    client.create(&owner, &String::from_str(&env, "Doomed"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Doomed")).id;
 
     // Hunger decays by 1 every hour. It needs 100 hours to reach 0.
-    advance_ledger(&env, 360000);
+    // The pet falls sick at 20 hunger (hour 80), loses 2 health per hour
+    // from then on and 10 more once it starves, so health runs out at hour 104.
+    advance_ledger(&env, 3600 * 103);
 
     // Calling any function will trigger the decay calculation.
-    let pet = client.get_pet(&owner);
-    assert_eq!(pet.is_alive, false);
+    let pet = client.get_pet(&pet_id);
+    assert!(pet.is_alive);
+    assert!(pet.is_sick);
     assert_eq!(pet.hunger, 0);
+    assert_eq!(pet.health, 12);
+
+    advance_ledger(&env, 3600);
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_alive);
+    assert_eq!(pet.health, 0);
+    assert_eq!(pet.status, LifeStatus::Dead(DeathCause::Starvation));
 }
 
 #[test]
-#[should_panic(expected = "Your pet is no longer with us.")]
 fn test_action_on_dead_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    client.create(&owner, &String::from_str(&env, "Ghost"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Ghost")).id;
 
-    // Advance time enough to kill the pet (100 hours for hunger)
-    advance_ledger(&env, 360000);
+    // Advance time enough to kill the pet (104 hours without food)
+    advance_ledger(&env, 3600 * 104);
 
     // This call will update the state to dead
-    let pet = client.get_pet(&owner);
-    assert_eq!(pet.is_alive, false);
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_alive);
 
-    // This should panic
-    client.feed(&owner);
+    assert_eq!(
+        client.try_feed(&owner, &pet_id, &None),
+        Err(Ok(Error::PetDead))
+    );
 }
 
 #[test]
@@ -198,18 +351,19 @@ fn test_mint_glasses() {
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    client.create(&owner, &String::from_str(&env, "Cool"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Cool")).id;
 
     // Work to earn coins (need at least 50 for glasses)
-    client.work(&owner); // 25 coins
-    client.work(&owner); // 50 coins
+    client.work(&owner, &pet_id); // 25 coins
+    wait_for_cooldowns(&env);
+    client.work(&owner, &pet_id); // 50 coins
 
-    client.mint_glasses(&owner);
+    client.mint_glasses(&owner, &pet_id);
 
-    let pet = client.get_pet(&owner);
+    let pet = client.get_pet(&pet_id);
     let coins = client.get_coins(&owner);
 
-    assert_eq!(pet.has_glasses, true);
+    assert!(pet.has_glasses);
     assert_eq!(coins, 0);
 }
 
@@ -220,48 +374,58 @@ fn test_recreate_pet_after_death() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    // Create initial pet
-    client.create(&owner, &String::from_str(&env, "FirstPet"));
+    // Create initial pet and earn some coins with it
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "FirstPet"))
+        .id;
+    client.work(&owner, &pet_id);
 
-    let pet = client.get_pet(&owner);
-    assert_eq!(pet.is_alive, true);
+    let pet = client.get_pet(&pet_id);
+    assert!(pet.is_alive);
     assert_eq!(pet.name, String::from_str(&env, "FirstPet"));
 
     // Advance time to kill the pet (hunger decays by 1 every hour, needs 100 hours to reach 0)
-    advance_ledger(&env, 360000); // 100 hours
+    advance_ledger(&env, 3600 * 104); // 104 hours
 
     // Verify pet is dead by calling get_pet (this triggers decay calculation)
-    let dead_pet = client.get_pet(&owner);
-    assert_eq!(dead_pet.is_alive, false);
+    let dead_pet = client.get_pet(&pet_id);
+    assert!(!dead_pet.is_alive);
     assert_eq!(dead_pet.hunger, 0);
 
     // Now create a new pet
     let new_pet = client.create(&owner, &String::from_str(&env, "SecondPet"));
 
     // Verify new pet is alive and has correct stats
-    assert_eq!(new_pet.is_alive, true);
+    assert!(new_pet.is_alive);
     assert_eq!(new_pet.name, String::from_str(&env, "SecondPet"));
     assert_eq!(new_pet.hunger, MAX_STAT);
     assert_eq!(new_pet.happiness, MAX_STAT);
     assert_eq!(new_pet.energy, MAX_STAT);
-    assert_eq!(new_pet.has_glasses, false);
+    assert!(!new_pet.has_glasses);
 
-    // Verify coins are reset to 0 for new pet
+    // The new pet gets its own ID and the old one stays until removed
+    assert_ne!(new_pet.id, pet_id);
+    assert_eq!(client.list_pets(&owner), vec![&env, pet_id, new_pet.id]);
+    assert!(!client.get_pet(&pet_id).is_alive);
+
+    // Coins belong to the owner and survive the pet
     let coins = client.get_coins(&owner);
-    assert_eq!(coins, 0);
+    assert_eq!(coins, 25);
 }
 
 #[test]
-#[should_panic(expected = "Not enough coins to mint glasses.")]
 fn test_mint_glasses_no_coins() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
-    client.create(&owner, &String::from_str(&env, "Broke"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Broke")).id;
 
     // Try to mint glasses without earning any coins
-    client.mint_glasses(&owner); // Should panic
+    assert_eq!(
+        client.try_mint_glasses(&owner, &pet_id),
+        Err(Ok(Error::InsufficientCoins))
+    );
 }
 
 /* -------------------------------------------------------------------------- */
@@ -269,14 +433,12 @@ fn test_mint_glasses_no_coins() {
 /* -------------------------------------------------------------------------- */
 
 #[test]
-#[should_panic(expected = "Pet not found")]
 fn test_get_nonexistent_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
-    let owner = Address::generate(&env);
 
-    client.get_pet(&owner); // Should panic
+    assert_eq!(client.try_get_pet(&1), Err(Ok(Error::PetNotFound)));
 }
 
 #[test]
@@ -291,142 +453,178 @@ fn test_get_coins_nonexistent_user() {
 }
 
 #[test]
-#[should_panic(expected = "Pet not found")]
 fn test_feed_nonexistent_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.feed(&owner); // Should panic
+    assert_eq!(
+        client.try_feed(&owner, &1, &None),
+        Err(Ok(Error::PetNotFound))
+    );
 }
 
 #[test]
-#[should_panic(expected = "Pet not found")]
 fn test_play_nonexistent_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.play(&owner); // Should panic
+    assert_eq!(
+        client.try_play(&owner, &1, &None),
+        Err(Ok(Error::PetNotFound))
+    );
 }
 
 #[test]
-#[should_panic(expected = "Pet not found")]
 fn test_sleep_nonexistent_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.sleep(&owner); // Should panic
+    assert_eq!(client.try_sleep(&owner, &1), Err(Ok(Error::PetNotFound)));
+}
+
+#[test]
+fn test_clean_nonexistent_pet() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    assert_eq!(client.try_clean(&owner, &1), Err(Ok(Error::PetNotFound)));
 }
 
 #[test]
-#[should_panic(expected = "Pet not found")]
 fn test_work_nonexistent_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.work(&owner); // Should panic
+    assert_eq!(client.try_work(&owner, &1), Err(Ok(Error::PetNotFound)));
 }
 
 #[test]
-#[should_panic(expected = "Pet not found")]
 fn test_mint_glasses_nonexistent_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.mint_glasses(&owner); // Should panic
+    assert_eq!(
+        client.try_mint_glasses(&owner, &1),
+        Err(Ok(Error::PetNotFound))
+    );
 }
 
 #[test]
-#[should_panic(expected = "Your pet is no longer with us.")]
 fn test_play_dead_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "DeadPet"));
-    advance_ledger(&env, 360000); // Kill pet
-    client.get_pet(&owner); // Update death status
-    client.play(&owner); // Should panic
+    let pet_id = client.create(&owner, &String::from_str(&env, "DeadPet")).id;
+    advance_ledger(&env, 3600 * 104); // Kill pet
+    client.get_pet(&pet_id); // Update death status
+    assert_eq!(
+        client.try_play(&owner, &pet_id, &None),
+        Err(Ok(Error::PetDead))
+    );
 }
 
 #[test]
-#[should_panic(expected = "Your pet is no longer with us.")]
 fn test_sleep_dead_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "DeadPet"));
-    advance_ledger(&env, 360000); // Kill pet
-    client.get_pet(&owner); // Update death status
-    client.sleep(&owner); // Should panic
+    let pet_id = client.create(&owner, &String::from_str(&env, "DeadPet")).id;
+    advance_ledger(&env, 3600 * 104); // Kill pet
+    client.get_pet(&pet_id); // Update death status
+    assert_eq!(client.try_sleep(&owner, &pet_id), Err(Ok(Error::PetDead)));
+}
+
+#[test]
+fn test_clean_dead_pet() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "DeadPet")).id;
+    advance_ledger(&env, 3600 * 104); // Kill pet
+    client.get_pet(&pet_id); // Update death status
+    assert_eq!(client.try_clean(&owner, &pet_id), Err(Ok(Error::PetDead)));
 }
 
 #[test]
-#[should_panic(expected = "Your pet is no longer with us.")]
 fn test_work_dead_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "DeadPet"));
-    advance_ledger(&env, 360000); // Kill pet
-    client.get_pet(&owner); // Update death status
-    client.work(&owner); // Should panic
+    let pet_id = client.create(&owner, &String::from_str(&env, "DeadPet")).id;
+    advance_ledger(&env, 3600 * 104); // Kill pet
+    client.get_pet(&pet_id); // Update death status
+    assert_eq!(client.try_work(&owner, &pet_id), Err(Ok(Error::PetDead)));
 }
 
 #[test]
-#[should_panic(expected = "Your pet is no longer with us.")]
 fn test_mint_glasses_dead_pet() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "DeadPet"));
-    advance_ledger(&env, 360000); // Kill pet
-    client.get_pet(&owner); // Update death status
-    client.mint_glasses(&owner); // Should panic
+    let pet_id = client.create(&owner, &String::from_str(&env, "DeadPet")).id;
+    advance_ledger(&env, 3600 * 104); // Kill pet
+    client.get_pet(&pet_id); // Update death status
+    assert_eq!(
+        client.try_mint_glasses(&owner, &pet_id),
+        Err(Ok(Error::PetDead))
+    );
 }
 
 #[test]
-#[should_panic(expected = "Not enough energy to work.")]
 fn test_work_insufficient_energy_edge_case() {
     let env = Env::default();
     env.mock_all_auths();
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "Exhausted"));
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "Exhausted"))
+        .id;
 
-    // Drain energy to exactly 19 (below threshold of 20)
+    // Drain energy to exactly 20 within the first hour and a half, before
+    // being awake starts to cost anything
+    client.work(&owner, &pet_id); // 100 -> 80
     for _ in 0..4 {
-        client.work(&owner); // 100 -> 80 -> 60 -> 40 -> 20
+        client.play(&owner, &pet_id, &None); // 80 -> 65 -> 50 -> 35 -> 20
+        advance_ledger(&env, 1800);
     }
 
-    let pet = client.get_pet(&owner);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.energy, 20);
 
     // One more work should succeed
-    client.work(&owner); // 20 -> 0
+    client.work(&owner, &pet_id); // 20 -> 0
+    wait_for_cooldowns(&env);
 
-    let pet = client.get_pet(&owner);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.energy, 0);
 
-    // This should panic
-    client.work(&owner);
+    assert_eq!(
+        client.try_work(&owner, &pet_id),
+        Err(Ok(Error::InsufficientEnergy))
+    );
 }
 
 /* -------------------------------------------------------------------------- */
@@ -444,19 +642,19 @@ fn test_multiple_users_isolation() {
     let user3 = Address::generate(&env);
 
     // Create pets for different users
-    client.create(&user1, &String::from_str(&env, "Pet1"));
-    client.create(&user2, &String::from_str(&env, "Pet2"));
-    client.create(&user3, &String::from_str(&env, "Pet3"));
+    let pet_id1 = client.create(&user1, &String::from_str(&env, "Pet1")).id;
+    let pet_id2 = client.create(&user2, &String::from_str(&env, "Pet2")).id;
+    let pet_id3 = client.create(&user3, &String::from_str(&env, "Pet3")).id;
 
     // User1 works, user2 plays, user3 feeds
-    client.work(&user1);
-    client.play(&user2);
-    client.feed(&user3);
+    client.work(&user1, &pet_id1);
+    client.play(&user2, &pet_id2, &None);
+    client.feed(&user3, &pet_id3, &None);
 
     // Check isolation
-    let pet1 = client.get_pet(&user1);
-    let pet2 = client.get_pet(&user2);
-    let pet3 = client.get_pet(&user3);
+    let pet1 = client.get_pet(&pet_id1);
+    let pet2 = client.get_pet(&pet_id2);
+    let pet3 = client.get_pet(&pet_id3);
 
     // User1 should have less energy and happiness, more coins
     assert_eq!(pet1.energy, 80);
@@ -480,29 +678,104 @@ fn test_stat_boundaries() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "Boundary"));
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "Boundary"))
+        .id;
 
     // Test feeding when already at max
-    client.feed(&owner);
-    let pet = client.get_pet(&owner);
+    client.feed(&owner, &pet_id, &None);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.hunger, MAX_STAT); // Should stay at 100
 
     // Test playing when already at max happiness
-    client.play(&owner);
-    let pet = client.get_pet(&owner);
+    client.play(&owner, &pet_id, &None);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.happiness, MAX_STAT); // Should stay at 100
 
-    // Test sleeping when already at max energy
-    client.sleep(&owner);
-    let pet = client.get_pet(&owner);
-    assert_eq!(pet.energy, MAX_STAT); // Should stay at 100
+    // Test sleeping past max energy
+    client.sleep(&owner, &pet_id);
+    advance_ledger(&env, 3600 * 2);
+    client.wake(&owner, &pet_id);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, MAX_STAT); // 85 + 20 = 105, capped at 100
 
-    // Drain energy completely
-    for _ in 0..5 {
-        client.work(&owner);
+    // Work until exhausted, losing 1 more energy every 3 hours awake
+    for _ in 0..4 {
+        client.work(&owner, &pet_id);
+        wait_for_cooldowns(&env);
     }
-    let pet = client.get_pet(&owner);
-    assert_eq!(pet.energy, 0);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 18);
+    assert_eq!(
+        client.try_work(&owner, &pet_id),
+        Err(Ok(Error::InsufficientEnergy))
+    );
+}
+
+#[test]
+fn test_overfeeding_boundaries() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "Stuffed")).id;
+
+    // Feeding a full pet tops nothing up and sours its mood
+    client.feed(&owner, &pet_id, &None);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.hunger, MAX_STAT);
+    assert_eq!(pet.happiness, 90); // 100 - 10 overfeed penalty
+
+    // 10 hours later hunger sits exactly at the threshold: no penalty
+    advance_ledger(&env, 3600 * 10);
+    client.feed(&owner, &pet_id, &None);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.hunger, MAX_STAT); // 90 + 30, capped at 100
+    assert_eq!(pet.happiness, 85); // 90 - 5 decay
+
+    // 9 hours later hunger is still above the threshold
+    advance_ledger(&env, 3600 * 9);
+    client.feed(&owner, &pet_id, &None);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.hunger, MAX_STAT); // 91 + 30, capped at 100
+    assert_eq!(pet.happiness, 71); // 85 - 4 decay - 10 penalty
+}
+
+#[test]
+fn test_play_diminishing_returns() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "Bored")).id;
+
+    // Work to bring happiness down: 100 -> 90 -> 80 -> 70, then 1 point of
+    // decay over the 3 hours
+    for _ in 0..3 {
+        client.work(&owner, &pet_id);
+        wait_for_cooldowns(&env);
+    }
+
+    client.play(&owner, &pet_id, &None);
+    assert_eq!(client.get_pet(&pet_id).happiness, 89); // 69 + 20
+
+    advance_ledger(&env, 1800);
+    client.play(&owner, &pet_id, &None);
+    assert_eq!(client.get_pet(&pet_id).happiness, 99); // 89 + 20/2
+
+    // Playing again as soon as the cooldown allows keeps the streak going
+    advance_ledger(&env, 1800);
+    client.play(&owner, &pet_id, &None);
+    assert_eq!(client.get_pet(&pet_id).play_streak, 2);
+
+    // After a proper break the next play is worth the full amount again
+    advance_ledger(&env, 3600 * 4);
+    client.play(&owner, &pet_id, &None);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.play_streak, 0);
+    assert_eq!(pet.happiness, MAX_STAT);
 }
 
 #[test]
@@ -512,27 +785,127 @@ fn test_time_progression_accuracy() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "TimeTest"));
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "TimeTest"))
+        .id;
 
     // Test 1-hour progression
     advance_ledger(&env, 3600); // 1 hour
-    let pet = client.get_pet(&owner);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.hunger, 99); // 100 - 1
     assert_eq!(pet.happiness, 100); // 100 - 0 (decay every 2 hours)
 
     // Test 2-hour progression (total 3 hours)
     advance_ledger(&env, 3600 * 2); // 2 more hours
-    let pet = client.get_pet(&owner);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.hunger, 97); // 100 - 3
     assert_eq!(pet.happiness, 99); // 100 - 1 (3/2 = 1)
 
     // Test 24-hour progression (total 27 hours)
     advance_ledger(&env, 3600 * 24); // 24 more hours
-    let pet = client.get_pet(&owner);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.hunger, 73); // 100 - 27
     assert_eq!(pet.happiness, 87); // 100 - 13 (27/2 = 13)
 }
 
+#[test]
+fn test_energy_drains_while_awake() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "Drowsy")).id;
+
+    // Test 1-hour progression
+    advance_ledger(&env, 3600); // 1 hour
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 100); // 100 - 0 (drain every 3 hours)
+
+    // Test 3-hour progression (total 4 hours)
+    advance_ledger(&env, 3600 * 3); // 3 more hours
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 99); // 100 - 1
+
+    // Test 24-hour progression (total 28 hours)
+    advance_ledger(&env, 3600 * 24); // 24 more hours
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 91); // 99 - 8 (24/3 = 8)
+    assert!(pet.asleep_since.is_none());
+}
+
+#[test]
+fn test_energy_recovers_while_sleeping() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "Napper")).id;
+
+    // Drain energy through four hours of work, then put the pet to bed
+    for _ in 0..4 {
+        client.work(&owner, &pet_id);
+        wait_for_cooldowns(&env);
+    }
+    client.sleep(&owner, &pet_id);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 19);
+    assert_eq!(pet.hunger, 96);
+
+    // Test 2-hour progression
+    advance_ledger(&env, 3600 * 2); // 2 hours
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 39); // 19 + 2 * 10
+    assert_eq!(pet.hunger, 95); // 96 - 1 (hunger drops every 2 hours asleep)
+
+    // Test 10-hour progression (total 12 hours)
+    advance_ledger(&env, 3600 * 10); // 10 more hours
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, MAX_STAT); // 39 + 100 = 139, capped at 100
+    assert_eq!(pet.hunger, 90); // 95 - 5 (10/2 = 5)
+}
+
+#[test]
+fn test_sleeping_pet_must_be_woken() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "Alarm")).id;
+
+    assert_eq!(client.try_wake(&owner, &pet_id), Err(Ok(Error::NotAsleep)));
+
+    client.work(&owner, &pet_id); // Energy: 80
+    client.sleep(&owner, &pet_id);
+    assert_eq!(
+        client.try_sleep(&owner, &pet_id),
+        Err(Ok(Error::AlreadyAsleep))
+    );
+    assert_eq!(
+        client.try_feed(&owner, &pet_id, &None),
+        Err(Ok(Error::PetAsleep))
+    );
+    assert_eq!(
+        client.try_play(&owner, &pet_id, &None),
+        Err(Ok(Error::PetAsleep))
+    );
+    assert_eq!(client.try_work(&owner, &pet_id), Err(Ok(Error::PetAsleep)));
+
+    advance_ledger(&env, 3600);
+    client.wake(&owner, &pet_id); // Energy: 80 + 10 = 90
+    client.play(&owner, &pet_id, &None); // Energy: 90 - 15 = 75
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 75);
+    assert!(pet.asleep_since.is_none());
+
+    // Awake again, so time drains energy instead of restoring it
+    advance_ledger(&env, 3600 * 6); // 6 hours
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 73); // 75 - 2 (6/3 = 2)
+}
+
 #[test]
 fn test_death_by_happiness() {
     let env = Env::default();
@@ -540,14 +913,14 @@ fn test_death_by_happiness() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "Sad"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Sad")).id;
 
     // Feed regularly to keep hunger up, but don't play
     for _i in 0..50 {
         advance_ledger(&env, 3600 * 4); // 4 hours
 
         // Check if pet is dead first
-        let pet = client.get_pet(&owner);
+        let pet = client.get_pet(&pet_id);
         if !pet.is_alive {
             // Should die from happiness, not hunger
             assert!(pet.happiness == 0);
@@ -556,7 +929,7 @@ fn test_death_by_happiness() {
         }
 
         // Only feed if pet is still alive
-        client.feed(&owner); // Keep hunger high
+        client.feed(&owner, &pet_id, &None); // Keep hunger high
     }
     panic!("Pet should have died from low happiness");
 }
@@ -568,24 +941,25 @@ fn test_complex_action_sequence() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "Complex"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Complex")).id;
 
     // Simulate a balanced daily routine for a week
     for day in 1..=7 {
         // Morning: feed and play
-        client.feed(&owner);
-        client.play(&owner);
+        client.feed(&owner, &pet_id, &None);
+        client.play(&owner, &pet_id, &None);
 
         // Afternoon: work once (not twice to avoid energy drain)
-        client.work(&owner);
+        client.work(&owner, &pet_id);
 
         // Evening: sleep to recover
-        client.sleep(&owner);
+        client.sleep(&owner, &pet_id);
 
         // Let 8 hours pass (less aggressive time progression)
         advance_ledger(&env, 3600 * 8);
+        client.wake(&owner, &pet_id);
 
-        let pet = client.get_pet(&owner);
+        let pet = client.get_pet(&pet_id);
         assert!(pet.is_alive, "Pet died on day {}", day);
 
         // More reasonable stat ranges after balanced activities
@@ -626,26 +1000,27 @@ fn test_glasses_workflow() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "Stylish"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Stylish")).id;
 
     // Work exactly enough for glasses
-    client.work(&owner); // 25 coins
-    client.work(&owner); // 50 coins
+    client.work(&owner, &pet_id); // 25 coins
+    wait_for_cooldowns(&env);
+    client.work(&owner, &pet_id); // 50 coins
 
     let coins_before = client.get_coins(&owner);
     assert_eq!(coins_before, 50);
 
-    let pet_before = client.get_pet(&owner);
-    assert_eq!(pet_before.has_glasses, false);
+    let pet_before = client.get_pet(&pet_id);
+    assert!(!pet_before.has_glasses);
 
     // Buy glasses
-    client.mint_glasses(&owner);
+    client.mint_glasses(&owner, &pet_id);
 
     let coins_after = client.get_coins(&owner);
-    let pet_after = client.get_pet(&owner);
+    let pet_after = client.get_pet(&pet_id);
 
     assert_eq!(coins_after, 0);
-    assert_eq!(pet_after.has_glasses, true);
+    assert!(pet_after.has_glasses);
 }
 
 #[test]
@@ -655,11 +1030,11 @@ fn test_edge_case_zero_time_elapsed() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "Instant"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Instant")).id;
 
     // Get pet immediately without time advancement
-    let pet1 = client.get_pet(&owner);
-    let pet2 = client.get_pet(&owner);
+    let pet1 = client.get_pet(&pet_id);
+    let pet2 = client.get_pet(&pet_id);
 
     // Stats should be identical
     assert_eq!(pet1.hunger, pet2.hunger);
@@ -675,19 +1050,2624 @@ fn test_maximum_stat_decay() {
     let client = create_tamagotchi_contract(&env);
     let owner = Address::generate(&env);
 
-    client.create(&owner, &String::from_str(&env, "Maximum"));
+    let pet_id = client.create(&owner, &String::from_str(&env, "Maximum")).id;
 
     // Test hunger reaching exactly 0
     advance_ledger(&env, 3600 * 100); // 100 hours
-    let pet = client.get_pet(&owner);
+    let pet = client.get_pet(&pet_id);
     assert_eq!(pet.hunger, 0);
-    assert_eq!(pet.is_alive, false);
-
-    // Test happiness reaching exactly 0
-    let owner2 = Address::generate(&env);
-    client.create(&owner2, &String::from_str(&env, "Maximum2"));
-    advance_ledger(&env, 3600 * 200); // 200 hours (happiness decays every 2 hours)
-    let pet2 = client.get_pet(&owner2);
-    assert_eq!(pet2.happiness, 0);
-    assert_eq!(pet2.is_alive, false);
+    assert!(pet.is_alive); // Health outlasts hunger for a few hours
+
+    // Test health reaching exactly 0
+    advance_ledger(&env, 3600 * 200); // 200 more hours
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.health, 0);
+    assert!(!pet.is_alive);
+
+    // Stats freeze once the pet is gone
+    assert_eq!(pet.happiness, 48); // 100 - 104/2 at the time of death
+}
+
+/* -------------------------------------------------------------------------- */
+/*                           STORAGE AND TTL TESTS                            */
+/* -------------------------------------------------------------------------- */
+
+fn advance_sequence(env: &Env, ledgers: u32) {
+    env.ledger().with_mut(|li| {
+        li.sequence_number += ledgers;
+        // `advance_ledger` lifts the TTL cap to u32::MAX, keep the highest
+        // reachable ledger representable once the sequence moves
+        li.max_entry_ttl = li.max_entry_ttl.min(u32::MAX - li.sequence_number);
+    });
+}
+
+fn pet_ttl(env: &Env, client: &TamagotchiContractClient, pet_id: u64) -> u32 {
+    env.as_contract(&client.address, || {
+        env.storage().persistent().get_ttl(&DataKey::Pet(pet_id))
+    })
+}
+
+#[test]
+fn test_player_data_in_persistent_storage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "Saved")).id;
+    client.work(&owner, &pet_id);
+
+    env.as_contract(&client.address, || {
+        let pet_key = DataKey::Pet(pet_id);
+        let coins_key = DataKey::Coins(owner.clone());
+
+        assert!(!env.storage().instance().has(&pet_key));
+        assert!(!env.storage().instance().has(&coins_key));
+        assert!(env.storage().persistent().has(&pet_key));
+        assert!(env.storage().persistent().has(&coins_key));
+        assert_eq!(
+            env.storage().persistent().get_ttl(&pet_key),
+            PLAYER_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&coins_key),
+            PLAYER_BUMP_AMOUNT
+        );
+    });
+}
+
+#[test]
+fn test_actions_extend_pet_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "Evergreen"))
+        .id;
+    assert_eq!(pet_ttl(&env, &client, pet_id), PLAYER_BUMP_AMOUNT);
+
+    // Let the entry age past the bump threshold before each touch
+    advance_sequence(&env, 2 * DAY_IN_LEDGERS);
+    assert!(pet_ttl(&env, &client, pet_id) < PLAYER_LIFETIME_THRESHOLD);
+    client.feed(&owner, &pet_id, &None);
+    assert_eq!(pet_ttl(&env, &client, pet_id), PLAYER_BUMP_AMOUNT);
+
+    advance_sequence(&env, 2 * DAY_IN_LEDGERS);
+    client.play(&owner, &pet_id, &None);
+    assert_eq!(pet_ttl(&env, &client, pet_id), PLAYER_BUMP_AMOUNT);
+
+    advance_sequence(&env, 2 * DAY_IN_LEDGERS);
+    client.sleep(&owner, &pet_id);
+    assert_eq!(pet_ttl(&env, &client, pet_id), PLAYER_BUMP_AMOUNT);
+
+    advance_sequence(&env, 2 * DAY_IN_LEDGERS);
+    client.wake(&owner, &pet_id);
+    assert_eq!(pet_ttl(&env, &client, pet_id), PLAYER_BUMP_AMOUNT);
+
+    advance_sequence(&env, 2 * DAY_IN_LEDGERS);
+    client.work(&owner, &pet_id);
+    assert_eq!(pet_ttl(&env, &client, pet_id), PLAYER_BUMP_AMOUNT);
+
+    advance_sequence(&env, 2 * DAY_IN_LEDGERS);
+    client.get_pet(&pet_id);
+    assert_eq!(pet_ttl(&env, &client, pet_id), PLAYER_BUMP_AMOUNT);
+}
+
+#[test]
+fn test_remove_pet_clears_player_data() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client.create(&owner, &String::from_str(&env, "Gone")).id;
+    client.work(&owner, &pet_id);
+    client.remove_pet(&owner, &pet_id);
+
+    assert_eq!(client.debug_pet_status(&pet_id), (false, false, 0, 0));
+    assert_eq!(client.list_pets(&owner), Vec::new(&env));
+    assert_eq!(client.get_coins(&owner), 25);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().persistent().has(&DataKey::Pet(pet_id)));
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&DataKey::OwnerPets(owner.clone())));
+    });
+}
+
+#[test]
+fn test_remove_pet_returns_accessories() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = owner_with_coins(&env, &client, 4);
+    let pet_id = client.list_pets(&owner).get_unchecked(0);
+    client.mint_glasses(&owner, &pet_id);
+
+    client.remove_pet(&owner, &pet_id);
+    assert_eq!(
+        client.get_inventory(&owner),
+        map![&env, (symbol_short!("glasses"), 1)]
+    );
+
+    // A dead pet keeps its accessories in the grave
+    let pet_id = client.create(&owner, &String::from_str(&env, "Dapper")).id;
+    client.equip_item(&owner, &pet_id, &symbol_short!("glasses"));
+    advance_ledger(&env, 3600 * 104);
+    client.remove_pet(&owner, &pet_id);
+    assert_eq!(client.get_inventory(&owner), map![&env]);
+    let graveyard = client.get_graveyard(&owner, &0, &10);
+    assert_eq!(
+        graveyard.get_unchecked(graveyard.len() - 1).accessories,
+        vec![&env, symbol_short!("glasses")]
+    );
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                EVENT TESTS                                 */
+/* -------------------------------------------------------------------------- */
+
+fn contract_event(
+    env: &Env,
+    client: &TamagotchiContractClient,
+    event: impl Event,
+) -> (Address, Vec<Val>, Val) {
+    (client.address.clone(), event.topics(env), event.data(env))
+}
+
+#[test]
+fn test_create_emits_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let name = String::from_str(&env, "Herald");
+
+    let pet_id = client.create(&owner, &name).id;
+
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                PetCreated {
+                    owner: owner.clone(),
+                    pet_id,
+                    name,
+                    birthdate: env.ledger().timestamp(),
+                },
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_actions_emit_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Busy")).id;
+
+    // 10 hours of decay: hunger 90, happiness 95, energy 97, cleanliness 95
+    advance_ledger(&env, 3600 * 10);
+    client.feed(&owner, &pet_id, &None);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                ActionPerformed {
+                    owner: owner.clone(),
+                    pet_id,
+                    action: symbol_short!("feed"),
+                    before: PetStats {
+                        hunger: 90,
+                        happiness: 95,
+                        energy: 97,
+                        health: 100,
+                        cleanliness: 95,
+                    },
+                    after: PetStats {
+                        hunger: 100,
+                        happiness: 95,
+                        energy: 97,
+                        health: 100,
+                        cleanliness: 95,
+                    },
+                },
+            ),
+        ]
+    );
+
+    client.play(&owner, &pet_id, &None);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                ActionPerformed {
+                    owner: owner.clone(),
+                    pet_id,
+                    action: symbol_short!("play"),
+                    before: PetStats {
+                        hunger: 100,
+                        happiness: 95,
+                        energy: 97,
+                        health: 100,
+                        cleanliness: 95,
+                    },
+                    after: PetStats {
+                        hunger: 100,
+                        happiness: 100,
+                        energy: 82,
+                        health: 100,
+                        cleanliness: 95,
+                    },
+                },
+            ),
+        ]
+    );
+
+    client.sleep(&owner, &pet_id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                ActionPerformed {
+                    owner: owner.clone(),
+                    pet_id,
+                    action: symbol_short!("sleep"),
+                    before: PetStats {
+                        hunger: 100,
+                        happiness: 100,
+                        energy: 82,
+                        health: 100,
+                        cleanliness: 95,
+                    },
+                    after: PetStats {
+                        hunger: 100,
+                        happiness: 100,
+                        energy: 82,
+                        health: 100,
+                        cleanliness: 95,
+                    },
+                },
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_economy_emits_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Banker")).id;
+
+    client.work(&owner, &pet_id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                ActionPerformed {
+                    owner: owner.clone(),
+                    pet_id,
+                    action: symbol_short!("work"),
+                    before: PetStats {
+                        hunger: 100,
+                        happiness: 100,
+                        energy: 100,
+                        health: 100,
+                        cleanliness: 100,
+                    },
+                    after: PetStats {
+                        hunger: 100,
+                        happiness: 90,
+                        energy: 80,
+                        health: 100,
+                        cleanliness: 100,
+                    },
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                Mint {
+                    to: owner.clone(),
+                    amount: 25,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                CoinsEarned {
+                    owner: owner.clone(),
+                    amount: 25,
+                    balance: 25,
+                },
+            ),
+        ]
+    );
+
+    wait_for_cooldowns(&env);
+    client.work(&owner, &pet_id);
+    client.mint_glasses(&owner, &pet_id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Burn {
+                    from: owner.clone(),
+                    amount: 50,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                CoinsSpent {
+                    owner: owner.clone(),
+                    amount: 50,
+                    balance: 0,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                AccessoryMinted {
+                    owner: owner.clone(),
+                    pet_id,
+                    accessory: symbol_short!("glasses"),
+                    price: 50,
+                },
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_death_emits_event_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Fading")).id;
+
+    advance_ledger(&env, 3600 * 104);
+    client.get_pet(&pet_id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                PetDied {
+                    owner: owner.clone(),
+                    pet_id,
+                    cause: DeathCause::Starvation,
+                    died_at: env.ledger().timestamp(),
+                    stats: PetStats {
+                        hunger: 0,
+                        happiness: 48,
+                        energy: 66,
+                        health: 0,
+                        cleanliness: 48,
+                    },
+                },
+            ),
+        ]
+    );
+
+    // Observing the dead pet again must not report a second death
+    advance_ledger(&env, 3600);
+    client.get_pet(&pet_id);
+    assert_eq!(env.events().all().len(), 0);
+}
+
+#[test]
+fn test_death_by_sadness_event_cause() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Gloomy")).id;
+
+    // Keep the pet fed and clean while happiness runs out, twice a day so
+    // it is never overfed
+    for _ in 0..16 {
+        advance_ledger(&env, 3600 * 12);
+        client.feed(&owner, &pet_id, &None);
+        client.clean(&owner, &pet_id);
+    }
+    advance_ledger(&env, 3600 * 12);
+    let pet = client.get_pet(&pet_id);
+    // Noticed at hour 204, the pet died 3 hours before that
+    assert_eq!(pet.died_at, Some(3600 * 201));
+
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                PetDied {
+                    owner: owner.clone(),
+                    pet_id,
+                    cause: DeathCause::Sadness,
+                    died_at: 3600 * 201,
+                    stats: PetStats {
+                        hunger: 91,
+                        happiness: 0,
+                        energy: 33,
+                        health: 0,
+                        cleanliness: 87,
+                    },
+                },
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_death_time_does_not_depend_on_observation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let early = client.create(&owner, &String::from_str(&env, "Early")).id;
+    let late = client.create(&owner, &String::from_str(&env, "Late")).id;
+
+    advance_ledger(&env, 3600 * 104);
+    client.get_pet(&early);
+    advance_ledger(&env, 3600 * 24 * 7);
+    client.get_pet(&late);
+
+    for pet_id in [early, late] {
+        let pet = client.get_pet(&pet_id);
+        assert_eq!(pet.died_at, Some(3600 * 104));
+    }
+    let graves = client.get_graveyard(&owner, &0, &10);
+    assert_eq!(graves.get_unchecked(0).died_at, 3600 * 104);
+    assert_eq!(graves.get_unchecked(1).died_at, 3600 * 104);
+}
+
+#[test]
+fn test_remove_pet_emits_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "Farewell"))
+        .id;
+
+    client.remove_pet(&owner, &pet_id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                PetRemoved {
+                    owner: owner.clone(),
+                    pet_id,
+                    was_alive: true,
+                },
+            ),
+        ]
+    );
+
+    // Removing again fails and stays silent
+    assert_eq!(
+        client.try_remove_pet(&owner, &pet_id),
+        Err(Ok(Error::PetNotFound))
+    );
+    assert_eq!(env.events().all().len(), 0);
+}
+
+/* -------------------------------------------------------------------------- */
+/*                          ADMIN AND UPGRADE TESTS                           */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn test_constructor_records_admin_and_version() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let client = TamagotchiContractClient::new(&env, &env.register(TamagotchiContract, (&admin,)));
+
+    assert_eq!(client.get_admin(), admin);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+}
+
+#[test]
+fn test_set_admin_requires_admin_auth() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let admin = client.get_admin();
+    let new_admin = Address::generate(&env);
+
+    client.set_admin(&new_admin);
+    assert_eq!(
+        env.auths(),
+        std::vec![(
+            admin,
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    client.address.clone(),
+                    Symbol::new(&env, "set_admin"),
+                    (&new_admin,).into_val(&env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(client.get_admin(), new_admin);
+}
+
+#[test]
+fn test_upgrade_rejects_non_admin() {
+    let env = Env::default();
+    let client = create_tamagotchi_contract(&env);
+    let intruder = Address::generate(&env);
+    let wasm_hash = BytesN::from_array(&env, &[7; 32]);
+
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &intruder,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "upgrade",
+                args: (&wasm_hash,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_upgrade(&wasm_hash);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_migrate_keeps_player_data() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "Survivor"))
+        .id;
+    client.work(&owner, &pet_id);
+    let pet_before = client.get_pet(&pet_id);
+
+    // Pretend the stored data predates the current layout
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::SchemaVersion, &0u32)
+    });
+    assert_eq!(client.migrate(), SCHEMA_VERSION);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+
+    assert_eq!(client.get_pet(&pet_id), pet_before);
+    assert_eq!(client.get_coins(&owner), 25);
+}
+
+#[test]
+fn test_migrate_refuses_newer_schema() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &(SCHEMA_VERSION + 1))
+    });
+    assert_eq!(
+        client.try_migrate(),
+        Err(Ok(Error::UnsupportedSchemaVersion))
+    );
+}
+
+/* -------------------------------------------------------------------------- */
+/*                           GAME CONFIGURATION TESTS                         */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn test_default_config() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(TamagotchiContract, (&admin,));
+    let client = TamagotchiContractClient::new(&env, &contract_id);
+
+    let config = client.get_config();
+    assert_eq!(config, GameConfig::default());
+    assert_eq!(config.max_stat, MAX_STAT);
+    assert_eq!(config.decay_period, 3600);
+    assert_eq!(config.work_reward, 25);
+    assert_eq!(client.get_item(&symbol_short!("glasses")).price, 50);
+}
+
+#[test]
+fn test_set_config_rebalances_live_game() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Tuned")).id;
+
+    let config = GameConfig {
+        decay_period: 1800,
+        feed_hunger: 5,
+        work_energy_cost: 30,
+        work_happiness_cost: 0,
+        work_cooldown: 0,
+        work_reward: 40,
+        ..GameConfig::ageless()
+    };
+    client.set_config(&config);
+    assert_eq!(client.get_config(), config);
+    client.set_item(&Item {
+        price: 80,
+        ..client.get_item(&symbol_short!("glasses"))
+    });
+
+    // Hunger now drops every half hour
+    advance_ledger(&env, 3600 * 5);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.hunger, 90);
+    assert_eq!(pet.happiness, 95);
+
+    client.feed(&owner, &pet_id, &None);
+    assert_eq!(client.get_pet(&pet_id).hunger, 95);
+
+    for _ in 0..3 {
+        client.work(&owner, &pet_id);
+    }
+    // 5 hours awake drained 3 energy before the shifts
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.energy, 7);
+    assert_eq!(pet.happiness, 95);
+    assert_eq!(client.get_coins(&owner), 120);
+    assert_eq!(
+        client.try_work(&owner, &pet_id),
+        Err(Ok(Error::InsufficientEnergy))
+    );
+
+    client.mint_glasses(&owner, &pet_id);
+    assert_eq!(client.get_coins(&owner), 40);
+}
+
+#[test]
+fn test_set_config_rejects_invalid_values() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+
+    let no_decay_period = GameConfig {
+        decay_period: 0,
+        ..GameConfig::ageless()
+    };
+    assert_eq!(
+        client.try_set_config(&no_decay_period),
+        Err(Ok(Error::InvalidConfig))
+    );
+
+    // Stages have to follow each other
+    let mut stages_out_of_order = GameConfig::default();
+    stages_out_of_order.stages.teen.ends_at = 0;
+    assert_eq!(
+        client.try_set_config(&stages_out_of_order),
+        Err(Ok(Error::InvalidConfig))
+    );
+
+    let negative_price = GameConfig {
+        medicine_price: -1,
+        ..GameConfig::ageless()
+    };
+    assert_eq!(
+        client.try_set_config(&negative_price),
+        Err(Ok(Error::InvalidConfig))
+    );
+
+    // Revived pets come back alive and within bounds
+    for revive_stat in [0, MAX_STAT + 1] {
+        let config = GameConfig {
+            revive_stat,
+            ..GameConfig::ageless()
+        };
+        assert_eq!(
+            client.try_set_config(&config),
+            Err(Ok(Error::InvalidConfig))
+        );
+    }
+    assert_eq!(client.get_config(), GameConfig::ageless());
+}
+
+#[test]
+fn test_set_config_rejects_non_admin() {
+    let env = Env::default();
+    let client = create_tamagotchi_contract(&env);
+    let intruder = Address::generate(&env);
+    let config = GameConfig {
+        work_reward: 1_000,
+        ..GameConfig::ageless()
+    };
+
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &intruder,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "set_config",
+                args: (&config,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_config(&config);
+    assert!(result.is_err());
+    assert_eq!(client.get_config(), GameConfig::ageless());
+}
+
+/* -------------------------------------------------------------------------- */
+/*                              READ-ONLY VIEW TESTS                          */
+/* -------------------------------------------------------------------------- */
+
+fn stored_pet(env: &Env, client: &TamagotchiContractClient, pet_id: u64) -> Pet {
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Pet(pet_id))
+            .unwrap()
+    })
+}
+
+#[test]
+fn test_view_pet_leaves_storage_untouched() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Window")).id;
+    let stored_before = stored_pet(&env, &client, pet_id);
+
+    advance_ledger(&env, 3600 * 10);
+    advance_sequence(&env, 2 * DAY_IN_LEDGERS);
+    let ttl_before = pet_ttl(&env, &client, pet_id);
+
+    let viewed = client.view_pet(&pet_id);
+    assert_eq!(viewed.hunger, 90);
+    assert_eq!(viewed.happiness, 95);
+    assert_eq!(viewed.last_updated, env.ledger().timestamp());
+
+    // Neither the pet nor its TTL changed
+    assert_eq!(stored_pet(&env, &client, pet_id), stored_before);
+    assert_eq!(pet_ttl(&env, &client, pet_id), ttl_before);
+
+    // The mutating read persists exactly what the view reported
+    assert_eq!(client.get_pet(&pet_id), viewed);
+    assert_eq!(stored_pet(&env, &client, pet_id), viewed);
+}
+
+#[test]
+fn test_view_pet_reports_death_without_persisting() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Quiet")).id;
+
+    advance_ledger(&env, 3600 * 104);
+    let viewed = client.view_pet(&pet_id);
+    assert!(!viewed.is_alive);
+    assert_eq!(viewed.hunger, 0);
+    assert_eq!(env.events().all().len(), 0);
+    assert!(stored_pet(&env, &client, pet_id).is_alive);
+
+    assert_eq!(client.debug_pet_status(&pet_id), (true, false, 0, 48));
+    assert!(stored_pet(&env, &client, pet_id).is_alive);
+}
+
+#[test]
+fn test_view_nonexistent_pet() {
+    let env = Env::default();
+    let client = create_tamagotchi_contract(&env);
+
+    assert_eq!(client.try_view_pet(&1), Err(Ok(Error::PetNotFound)));
+}
+
+/* -------------------------------------------------------------------------- */
+/*                              COOLDOWN TESTS                                */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn test_repeated_feed_is_on_cooldown() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Glutton")).id;
+
+    advance_ledger(&env, 3600 * 50); // Hunger: 50
+    client.feed(&owner, &pet_id, &None); // Hunger: 80
+    assert_eq!(
+        client.try_feed(&owner, &pet_id, &None),
+        Err(Ok(Error::ActionOnCooldown))
+    );
+
+    // Other actions keep their own cooldowns
+    client.play(&owner, &pet_id, &None);
+
+    advance_ledger(&env, 1799);
+    assert_eq!(
+        client.try_feed(&owner, &pet_id, &None),
+        Err(Ok(Error::ActionOnCooldown))
+    );
+
+    advance_ledger(&env, 1);
+    client.feed(&owner, &pet_id, &None);
+    assert_eq!(client.get_pet(&pet_id).hunger, MAX_STAT);
+}
+
+#[test]
+fn test_work_cannot_be_farmed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Intern")).id;
+
+    client.work(&owner, &pet_id);
+    for _ in 0..5 {
+        assert_eq!(
+            client.try_work(&owner, &pet_id),
+            Err(Ok(Error::ActionOnCooldown))
+        );
+    }
+    assert_eq!(client.get_coins(&owner), 25);
+    assert_eq!(client.get_pet(&pet_id).energy, 80);
+
+    wait_for_cooldowns(&env);
+    client.work(&owner, &pet_id);
+    assert_eq!(client.get_coins(&owner), 50);
+}
+
+#[test]
+fn test_cooldowns_report_seconds_remaining() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Timer")).id;
+
+    let ready = ActionCooldowns {
+        feed: 0,
+        play: 0,
+        work: 0,
+    };
+    assert_eq!(client.cooldowns(&pet_id), ready);
+
+    client.feed(&owner, &pet_id, &None);
+    client.work(&owner, &pet_id);
+    advance_ledger(&env, 600);
+    assert_eq!(
+        client.cooldowns(&pet_id),
+        ActionCooldowns {
+            feed: 1200,
+            play: 0,
+            work: 3000,
+        }
+    );
+
+    advance_ledger(&env, 3000);
+    assert_eq!(client.cooldowns(&pet_id), ready);
+}
+
+#[test]
+fn test_cooldowns_nonexistent_pet() {
+    let env = Env::default();
+    let client = create_tamagotchi_contract(&env);
+
+    assert_eq!(client.try_cooldowns(&1), Err(Ok(Error::PetNotFound)));
+}
+
+/* -------------------------------------------------------------------------- */
+/*                          HEALTH AND SICKNESS TESTS                         */
+/* -------------------------------------------------------------------------- */
+
+// Overfeed a full pet until it falls sick, one meal per feed cooldown
+fn overfeed_until_sick(env: &Env, client: &TamagotchiContractClient, owner: &Address, pet_id: u64) {
+    for _ in 0..3 {
+        client.feed(owner, &pet_id, &None);
+        advance_ledger(env, 1800);
+    }
+    assert!(client.get_pet(&pet_id).is_sick);
+}
+
+#[test]
+fn test_give_medicine_cures_sickness() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Patient")).id;
+
+    client.work(&owner, &pet_id);
+    wait_for_cooldowns(&env);
+    client.work(&owner, &pet_id); // 50 coins
+    overfeed_until_sick(&env, &client, &owner, pet_id);
+
+    // Sickness costs 2 health an hour
+    advance_ledger(&env, 3600 * 20);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.health, 60);
+
+    client.give_medicine(&owner, &pet_id);
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_sick);
+    assert_eq!(pet.health, 80); // 60 + 20
+    assert_eq!(client.get_coins(&owner), 20);
+
+    // Healthy again, so health slowly comes back
+    advance_ledger(&env, 3600 * 5);
+    assert_eq!(client.get_pet(&pet_id).health, 85);
+}
+
+#[test]
+fn test_give_medicine_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "Hypochondriac"))
+        .id;
+
+    assert_eq!(
+        client.try_give_medicine(&owner, &pet_id),
+        Err(Ok(Error::NotSick))
+    );
+    assert_eq!(
+        client.try_give_medicine(&owner, &2),
+        Err(Ok(Error::PetNotFound))
+    );
+
+    // Without coins the cure is rolled back
+    overfeed_until_sick(&env, &client, &owner, pet_id);
+    assert_eq!(
+        client.try_give_medicine(&owner, &pet_id),
+        Err(Ok(Error::InsufficientCoins))
+    );
+    assert!(client.get_pet(&pet_id).is_sick);
+}
+
+#[test]
+fn test_untreated_sickness_is_fatal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client
+        .create(&owner, &String::from_str(&env, "Feverish"))
+        .id;
+
+    overfeed_until_sick(&env, &client, &owner, pet_id);
+
+    // 100 health at 2 an hour, long before hunger runs out
+    advance_ledger(&env, 3600 * 50);
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_alive);
+    assert_eq!(pet.status, LifeStatus::Dead(DeathCause::Sickness));
+    assert!(pet.hunger > 0);
+    assert!(pet.happiness > 0);
+    assert_eq!(
+        client.try_give_medicine(&owner, &pet_id),
+        Err(Ok(Error::PetDead))
+    );
+}
+
+#[test]
+fn test_give_medicine_emits_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Pills")).id;
+
+    client.work(&owner, &pet_id); // 25 coins, happiness 90
+    wait_for_cooldowns(&env);
+    client.work(&owner, &pet_id); // 50 coins, happiness 80
+    overfeed_until_sick(&env, &client, &owner, pet_id);
+    let before = client.get_pet(&pet_id).stats();
+
+    client.give_medicine(&owner, &pet_id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                ActionPerformed {
+                    owner: owner.clone(),
+                    pet_id,
+                    action: symbol_short!("medicine"),
+                    after: PetStats {
+                        health: MAX_STAT,
+                        ..before.clone()
+                    },
+                    before,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                Burn {
+                    from: owner.clone(),
+                    amount: 30,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                CoinsSpent {
+                    owner: owner.clone(),
+                    amount: 30,
+                    balance: 20,
+                },
+            ),
+        ]
+    );
+}
+
+/* -------------------------------------------------------------------------- */
+/*                              LIFE STAGE TESTS                              */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn test_pet_grows_up() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    client.set_config(&GameConfig::default());
+    let owner = Address::generate(&env);
+
+    let pet = client.create(&owner, &String::from_str(&env, "Eggbert"));
+    let pet_id = pet.id;
+    assert_eq!(pet.stage, LifeStage::Egg);
+    assert_eq!(
+        client.try_feed(&owner, &pet_id, &None),
+        Err(Ok(Error::NotAllowedAtStage))
+    );
+
+    // Hatches after an hour, without having lost anything
+    advance_ledger(&env, 3600);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.stage, LifeStage::Baby);
+    assert_eq!(pet.hunger, MAX_STAT);
+    assert_eq!(pet.happiness, MAX_STAT);
+    assert_eq!(
+        client.try_work(&owner, &pet_id),
+        Err(Ok(Error::NotAllowedAtStage))
+    );
+    client.play(&owner, &pet_id, &None);
+
+    advance_ledger(&env, 3600 * 23);
+    assert_eq!(client.get_pet(&pet_id).stage, LifeStage::Child);
+    client.work(&owner, &pet_id);
+    assert_eq!(client.get_coins(&owner), 25);
+}
+
+#[test]
+fn test_pet_dies_of_old_age() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let mut config = GameConfig::ageless();
+    config.stages.adult.ends_at = 3600 * 5;
+    config.stages.elder.ends_at = 3600 * 10;
+    client.set_config(&config);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Gramps")).id;
+
+    advance_ledger(&env, 3600 * 5);
+    assert_eq!(client.get_pet(&pet_id).stage, LifeStage::Elder);
+
+    advance_ledger(&env, 3600 * 5);
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_alive);
+    assert_eq!(pet.status, LifeStatus::Dead(DeathCause::OldAge));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                PetDied {
+                    owner: owner.clone(),
+                    pet_id,
+                    cause: DeathCause::OldAge,
+                    died_at: env.ledger().timestamp(),
+                    stats: pet.stats(),
+                },
+            ),
+        ]
+    );
+}
+
+/* -------------------------------------------------------------------------- */
+/*                               EVOLUTION TESTS                              */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn test_pet_evolves_as_it_grows() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    client.set_config(&GameConfig::default());
+    let owner = Address::generate(&env);
+
+    let pet = client.create(&owner, &String::from_str(&env, "Morph"));
+    let pet_id = pet.id;
+    assert_eq!(pet.form, PetForm::Egg);
+    assert_eq!(client.evolution_history(&pet_id), vec![&env]);
+
+    advance_ledger(&env, 3600);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.form, PetForm::Blob);
+    let hatched = Evolution {
+        stage: LifeStage::Baby,
+        form: PetForm::Blob,
+        evolved_at: 3600,
+        missed_feedings: 0,
+        average_happiness: MAX_STAT,
+        times_worked: 0,
+    };
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                PetEvolved {
+                    owner: owner.clone(),
+                    pet_id,
+                    evolution: hatched.clone(),
+                },
+            ),
+        ]
+    );
+
+    // A well fed and entertained baby grows into a sprout
+    for _ in 0..23 {
+        client.play(&owner, &pet_id, &None);
+        advance_ledger(&env, 3600);
+    }
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.stage, LifeStage::Child);
+    assert_eq!(pet.form, PetForm::Sprout);
+
+    let history = client.evolution_history(&pet_id);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get_unchecked(0), hatched);
+    assert_eq!(history.get_unchecked(1).form, PetForm::Sprout);
+}
+
+#[test]
+fn test_evolution_history_is_read_only() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    client.set_config(&GameConfig::default());
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Peek")).id;
+
+    // Pending evolutions show up before anything is written
+    advance_ledger(&env, 3600 * 24);
+    let history = client.evolution_history(&pet_id);
+    assert_eq!(history.len(), 2);
+    assert_eq!(client.view_pet(&pet_id).form, history.get_unchecked(1).form);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().persistent().has(&DataKey::Evolutions(pet_id)));
+    });
+
+    client.get_pet(&pet_id);
+    assert_eq!(client.evolution_history(&pet_id), history);
+
+    // Released pets take their history with them
+    client.remove_pet(&owner, &pet_id);
+    assert_eq!(
+        client.try_evolution_history(&pet_id),
+        Err(Ok(Error::PetNotFound))
+    );
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().persistent().has(&DataKey::Evolutions(pet_id)));
+    });
+}
+
+/* -------------------------------------------------------------------------- */
+/*                               GRAVEYARD TESTS                              */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn test_dead_pet_is_buried_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let name = String::from_str(&env, "Goldie");
+    let pet_id = client.create(&owner, &name).id;
+
+    client.work(&owner, &pet_id);
+    wait_for_cooldowns(&env);
+    client.work(&owner, &pet_id);
+    client.mint_glasses(&owner, &pet_id);
+    assert_eq!(client.get_graveyard(&owner, &0, &10), vec![&env]);
+
+    advance_ledger(&env, 3600 * 104);
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_alive);
+    let record = DeathRecord {
+        pet_id,
+        name,
+        birthdate: 0,
+        died_at: 3600 * 104,
+        age: 3600 * 104,
+        cause: DeathCause::Starvation,
+        stage: LifeStage::Adult,
+        form: PetForm::Buddy,
+        stats: pet.stats(),
+        accessories: vec![&env, symbol_short!("glasses")],
+    };
+    assert_eq!(client.get_graveyard(&owner, &0, &10), vec![&env, record]);
+
+    client.get_pet(&pet_id);
+    client.remove_pet(&owner, &pet_id);
+    assert_eq!(client.get_graveyard(&owner, &0, &10).len(), 1);
+}
+
+#[test]
+fn test_removing_an_unnoticed_dead_pet_buries_it() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Ghost")).id;
+
+    advance_ledger(&env, 3600 * 104);
+    client.remove_pet(&owner, &pet_id);
+    let events = env.events().all();
+
+    let graves = client.get_graveyard(&owner, &0, &10);
+    assert_eq!(graves.len(), 1);
+    let record = graves.get_unchecked(0);
+    assert_eq!(record.cause, DeathCause::Starvation);
+    assert_eq!(
+        events,
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                PetDied {
+                    owner: owner.clone(),
+                    pet_id,
+                    cause: DeathCause::Starvation,
+                    died_at: record.died_at,
+                    stats: record.stats,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                PetRemoved {
+                    owner: owner.clone(),
+                    pet_id,
+                    was_alive: false,
+                },
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_graveyard_pagination() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let names = ["First", "Second", "Third"];
+    for name in names {
+        client.create(&owner, &String::from_str(&env, name));
+    }
+    advance_ledger(&env, 3600 * 104);
+    for pet_id in client.list_pets(&owner) {
+        client.get_pet(&pet_id);
+    }
+
+    let page = client.get_graveyard(&owner, &0, &2);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get_unchecked(0).name, String::from_str(&env, "First"));
+    assert_eq!(page.get_unchecked(1).name, String::from_str(&env, "Second"));
+
+    let page = client.get_graveyard(&owner, &2, &2);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).name, String::from_str(&env, "Third"));
+
+    assert_eq!(client.get_graveyard(&owner, &3, &2), vec![&env]);
+    assert_eq!(client.get_graveyard(&owner, &0, &0), vec![&env]);
+    assert_eq!(client.get_graveyard(&owner, &1, &u32::MAX).len(), 2);
+
+    // Graveyards are per owner
+    let stranger = Address::generate(&env);
+    assert_eq!(client.get_graveyard(&stranger, &0, &10), vec![&env]);
+}
+
+/* -------------------------------------------------------------------------- */
+/*                               REVIVAL TESTS                                */
+/* -------------------------------------------------------------------------- */
+
+// Earn the default revival price with four shifts, then let the pet starve
+fn work_then_die(env: &Env, client: &TamagotchiContractClient, owner: &Address) -> Pet {
+    let pet_id = client.create(owner, &String::from_str(env, "Lazarus")).id;
+    for _ in 0..4 {
+        client.work(owner, &pet_id);
+        wait_for_cooldowns(env);
+    }
+    assert_eq!(client.get_coins(owner), 100);
+
+    advance_ledger(env, 3600 * 100);
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_alive);
+    pet
+}
+
+#[test]
+fn test_revive_pet() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = work_then_die(&env, &client, &owner).id;
+
+    client.revive(&owner, &pet_id);
+    let events = env.events().all();
+
+    let pet = client.get_pet(&pet_id);
+    assert!(pet.is_alive);
+    assert_eq!(pet.status, LifeStatus::Alive);
+    assert_eq!(pet.died_at, None);
+    assert_eq!(pet.revivals, 1);
+    assert_eq!(pet.hunger, 50);
+    assert_eq!(pet.health, 50);
+    assert_eq!(client.get_coins(&owner), 0);
+    assert_eq!(
+        events,
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Burn {
+                    from: owner.clone(),
+                    amount: 100,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                CoinsSpent {
+                    owner: owner.clone(),
+                    amount: 100,
+                    balance: 0,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                PetRevived {
+                    owner: owner.clone(),
+                    pet_id,
+                    revivals: 1,
+                    stats: pet.stats(),
+                },
+            ),
+        ]
+    );
+
+    // The pet carries on where it left off and can be fed again
+    wait_for_cooldowns(&env);
+    client.feed(&owner, &pet_id, &None);
+    assert_eq!(
+        client.try_revive(&owner, &pet_id),
+        Err(Ok(Error::PetNotDead))
+    );
+}
+
+#[test]
+fn test_revive_takes_the_pet_out_of_the_graveyard() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let goner = client.create(&owner, &String::from_str(&env, "Goner")).id;
+    let pet_id = work_then_die(&env, &client, &owner).id;
+    assert!(!client.get_pet(&goner).is_alive);
+    let buried = || -> std::vec::Vec<u64> {
+        client
+            .get_graveyard(&owner, &0, &10)
+            .iter()
+            .map(|record| record.pet_id)
+            .collect()
+    };
+    assert_eq!(buried(), [pet_id, goner]);
+
+    client.revive(&owner, &pet_id);
+    assert_eq!(buried(), [goner]);
+
+    // Dying again digs a single new grave
+    advance_ledger(&env, 3600 * 100);
+    assert!(!client.get_pet(&pet_id).is_alive);
+    assert_eq!(buried(), [goner, pet_id]);
+}
+
+#[test]
+fn test_revive_needs_coins() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = client.create(&owner, &String::from_str(&env, "Broke")).id;
+
+    advance_ledger(&env, 3600 * 104);
+    client.get_pet(&pet_id);
+    assert_eq!(
+        client.try_revive(&owner, &pet_id),
+        Err(Ok(Error::InsufficientCoins))
+    );
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_alive);
+    assert_eq!(pet.revivals, 0);
+}
+
+#[test]
+fn test_revival_window_expires() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet = work_then_die(&env, &client, &owner);
+
+    // A day after the pet died, not after the death was noticed
+    let deadline = pet.died_at.unwrap() + 3600 * 24;
+    advance_ledger(&env, deadline - env.ledger().timestamp() + 1);
+    assert_eq!(
+        client.try_revive(&owner, &pet.id),
+        Err(Ok(Error::RevivalWindowExpired))
+    );
+    assert_eq!(client.get_coins(&owner), 100);
+}
+
+#[test]
+fn test_revive_someone_elses_pet() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = Address::generate(&env);
+    let pet_id = work_then_die(&env, &client, &owner).id;
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_revive(&stranger, &pet_id),
+        Err(Ok(Error::NotOwner))
+    );
+    assert_eq!(
+        client.try_revive(&owner, &(pet_id + 1)),
+        Err(Ok(Error::PetNotFound))
+    );
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                TOKEN TESTS                                 */
+/* -------------------------------------------------------------------------- */
+
+// An owner with `shifts` worth of work reward in the bank
+fn owner_with_coins(env: &Env, client: &TamagotchiContractClient, shifts: u32) -> Address {
+    let owner = Address::generate(env);
+    let pet_id = client.create(&owner, &String::from_str(env, "Earner")).id;
+    for _ in 0..shifts {
+        client.work(&owner, &pet_id);
+        wait_for_cooldowns(env);
+    }
+    owner
+}
+
+#[test]
+fn test_token_metadata() {
+    let env = Env::default();
+    let client = create_tamagotchi_contract(&env);
+    let token = TokenClient::new(&env, &client.address);
+
+    assert_eq!(token.name(), String::from_str(&env, "Tamago Coin"));
+    assert_eq!(token.symbol(), String::from_str(&env, "TAMA"));
+    assert_eq!(token.decimals(), 0);
+}
+
+#[test]
+fn test_game_coins_are_token_balances() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let token = TokenClient::new(&env, &client.address);
+    let owner = owner_with_coins(&env, &client, 2);
+    assert_eq!(token.balance(&owner), 50);
+
+    // Spending in the shop burns coins
+    let pet_id = client.list_pets(&owner).get_unchecked(0);
+    client.mint_glasses(&owner, &pet_id);
+    assert_eq!(token.balance(&owner), 0);
+    assert_eq!(client.get_coins(&owner), 0);
+
+    // Balances belong to the owner, not the pet
+    client.work(&owner, &pet_id);
+    client.remove_pet(&owner, &pet_id);
+    assert_eq!(token.balance(&owner), 25);
+    client.create(&owner, &String::from_str(&env, "Fresh"));
+    assert_eq!(token.balance(&owner), 25);
+}
+
+#[test]
+fn test_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let token = TokenClient::new(&env, &client.address);
+    let owner = owner_with_coins(&env, &client, 2);
+    let friend = Address::generate(&env);
+
+    token.transfer(&owner, &friend, &20);
+    assert_eq!(
+        env.auths(),
+        std::vec![(
+            owner.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    client.address.clone(),
+                    Symbol::new(&env, "transfer"),
+                    (owner.clone(), friend.clone(), 20_i128).into_val(&env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Transfer {
+                    from: owner.clone(),
+                    to: friend.clone(),
+                    amount: 20,
+                },
+            ),
+        ]
+    );
+    assert_eq!(token.balance(&owner), 30);
+    assert_eq!(token.balance(&friend), 20);
+
+    assert_eq!(
+        token.try_transfer(&owner, &friend, &31),
+        Err(Ok(Error::InsufficientCoins.into()))
+    );
+    assert_eq!(
+        token.try_transfer(&owner, &friend, &-1),
+        Err(Ok(Error::NegativeAmount.into()))
+    );
+    assert_eq!(token.balance(&owner), 30);
+}
+
+#[test]
+fn test_transfer_to_muxed_account() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let token = TokenClient::new(&env, &client.address);
+    let owner = owner_with_coins(&env, &client, 1);
+    let exchange = <MuxedAddress as MuxedAddressTestutils>::generate(&env);
+
+    token.transfer(&owner, &exchange, &25);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                TransferMuxed {
+                    from: owner.clone(),
+                    to: exchange.address(),
+                    to_muxed_id: exchange.id().unwrap(),
+                    amount: 25,
+                },
+            ),
+        ]
+    );
+    assert_eq!(token.balance(&exchange.address()), 25);
+}
+
+#[test]
+fn test_allowances() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let token = TokenClient::new(&env, &client.address);
+    let owner = owner_with_coins(&env, &client, 2);
+    let shop = Address::generate(&env);
+    let friend = Address::generate(&env);
+    let expiration_ledger = env.ledger().sequence() + 100;
+
+    token.approve(&owner, &shop, &30, &expiration_ledger);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Approve {
+                    from: owner.clone(),
+                    spender: shop.clone(),
+                    amount: 30,
+                    expiration_ledger,
+                },
+            ),
+        ]
+    );
+    assert_eq!(token.allowance(&owner, &shop), 30);
+
+    token.transfer_from(&shop, &owner, &friend, &20);
+    assert_eq!(token.allowance(&owner, &shop), 10);
+    assert_eq!(token.balance(&friend), 20);
+    assert_eq!(
+        token.try_transfer_from(&shop, &owner, &friend, &11),
+        Err(Ok(Error::InsufficientAllowance.into()))
+    );
+
+    token.burn_from(&shop, &owner, &10);
+    assert_eq!(token.allowance(&owner, &shop), 0);
+    assert_eq!(token.balance(&owner), 20);
+
+    // Spenders only ever draw on their own allowance
+    assert_eq!(
+        token.try_transfer_from(&friend, &owner, &friend, &1),
+        Err(Ok(Error::InsufficientAllowance.into()))
+    );
+}
+
+#[test]
+fn test_allowance_expires() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let token = TokenClient::new(&env, &client.address);
+    let owner = owner_with_coins(&env, &client, 1);
+    let spender = Address::generate(&env);
+    let expiration_ledger = env.ledger().sequence() + 10;
+
+    token.approve(&owner, &spender, &25, &expiration_ledger);
+    advance_sequence(&env, 10);
+    assert_eq!(token.allowance(&owner, &spender), 25);
+    advance_sequence(&env, 1);
+    assert_eq!(token.allowance(&owner, &spender), 0);
+
+    // Only clearing an allowance may use a ledger that has already passed
+    assert_eq!(
+        token.try_approve(&owner, &spender, &25, &expiration_ledger),
+        Err(Ok(Error::InvalidExpirationLedger.into()))
+    );
+    token.approve(&owner, &spender, &0, &0);
+}
+
+#[test]
+fn test_burn() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let token = TokenClient::new(&env, &client.address);
+    let owner = owner_with_coins(&env, &client, 1);
+
+    token.burn(&owner, &5);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Burn {
+                    from: owner.clone(),
+                    amount: 5,
+                },
+            ),
+        ]
+    );
+    assert_eq!(token.balance(&owner), 20);
+    assert_eq!(
+        token.try_burn(&owner, &21),
+        Err(Ok(Error::InsufficientCoins.into()))
+    );
+}
+
+/* -------------------------------------------------------------------------- */
+/*                               GIFTING TESTS                                */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn test_transfer_coins() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = owner_with_coins(&env, &client, 2);
+    let friend = Address::generate(&env);
+
+    client.transfer_coins(&owner, &friend, &50);
+    assert_eq!(
+        env.auths(),
+        std::vec![(
+            owner.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    client.address.clone(),
+                    Symbol::new(&env, "transfer_coins"),
+                    (owner.clone(), friend.clone(), 50_i128).into_val(&env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Transfer {
+                    from: owner.clone(),
+                    to: friend.clone(),
+                    amount: 50,
+                },
+            ),
+        ]
+    );
+    assert_eq!(client.get_coins(&owner), 0);
+    assert_eq!(client.get_coins(&friend), 50);
+
+    // The friend can spend the coins on their own pet right away
+    let pet_id = client.create(&friend, &String::from_str(&env, "Hungry")).id;
+    client.mint_glasses(&friend, &pet_id);
+    assert_eq!(client.get_coins(&friend), 0);
+}
+
+#[test]
+fn test_transfer_coins_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = owner_with_coins(&env, &client, 1);
+    let friend = Address::generate(&env);
+
+    assert_eq!(
+        client.try_transfer_coins(&owner, &friend, &26),
+        Err(Ok(Error::InsufficientCoins))
+    );
+    assert_eq!(
+        client.try_transfer_coins(&owner, &friend, &-5),
+        Err(Ok(Error::NegativeAmount))
+    );
+    assert_eq!(
+        client.try_transfer_coins(&owner, &owner, &5),
+        Err(Ok(Error::SelfTransfer))
+    );
+    assert_eq!(client.get_coins(&owner), 25);
+    assert_eq!(client.get_coins(&friend), 0);
+
+    // A balance that can't grow any further refuses the coins
+    env.as_contract(&client.address, || {
+        storage::write_coins(&env, &friend, i128::MAX)
+    });
+    assert_eq!(
+        client.try_transfer_coins(&owner, &friend, &1),
+        Err(Ok(Error::BalanceOverflow))
+    );
+    assert_eq!(client.get_coins(&owner), 25);
+}
+
+#[test]
+fn test_gift_item() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let giver = owner_with_coins(&env, &client, 2);
+    let friend = Address::generate(&env);
+    let pet_id = client.create(&friend, &String::from_str(&env, "Lucky")).id;
+
+    client.gift_item(&giver, &pet_id, &symbol_short!("glasses"));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Burn {
+                    from: giver.clone(),
+                    amount: 50,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                CoinsSpent {
+                    owner: giver.clone(),
+                    amount: 50,
+                    balance: 0,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                ItemGifted {
+                    from: giver.clone(),
+                    owner: friend.clone(),
+                    pet_id,
+                    item: symbol_short!("glasses"),
+                    price: 50,
+                },
+            ),
+        ]
+    );
+    assert!(client.get_pet(&pet_id).has_glasses);
+    assert_eq!(client.get_coins(&giver), 0);
+    assert_eq!(client.get_coins(&friend), 0);
+}
+
+#[test]
+fn test_gift_item_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let giver = owner_with_coins(&env, &client, 3);
+    let own_pet = client.list_pets(&giver).get_unchecked(0);
+    let friend = Address::generate(&env);
+    let pet_id = client.create(&friend, &String::from_str(&env, "Picky")).id;
+    let glasses = symbol_short!("glasses");
+
+    assert_eq!(
+        client.try_gift_item(&giver, &own_pet, &glasses),
+        Err(Ok(Error::SelfTransfer))
+    );
+    assert_eq!(
+        client.try_gift_item(&giver, &pet_id, &symbol_short!("hat")),
+        Err(Ok(Error::UnknownItem))
+    );
+    assert_eq!(
+        client.try_gift_item(&giver, &(pet_id + 1), &glasses),
+        Err(Ok(Error::PetNotFound))
+    );
+
+    client.gift_item(&giver, &pet_id, &glasses);
+    assert_eq!(
+        client.try_gift_item(&giver, &pet_id, &glasses),
+        Err(Ok(Error::ItemAlreadyOwned))
+    );
+    assert_eq!(client.get_coins(&giver), 25);
+
+    let pet_id = client.create(&friend, &String::from_str(&env, "Pricey")).id;
+    assert_eq!(
+        client.try_gift_item(&giver, &pet_id, &glasses),
+        Err(Ok(Error::InsufficientCoins))
+    );
+    assert!(!client.get_pet(&pet_id).has_glasses);
+}
+
+/* -------------------------------------------------------------------------- */
+/*                               TREASURY TESTS                               */
+/* -------------------------------------------------------------------------- */
+
+// A Stellar asset with 100 whole units (7 decimals, like XLM) in the buyer's
+// account, sold at one unit per coin
+fn priced_asset(env: &Env, client: &TamagotchiContractClient, buyer: &Address) -> Address {
+    let issuer = Address::generate(env);
+    let asset = env.register_stellar_asset_contract_v2(issuer).address();
+    StellarAssetClient::new(env, &asset).mint(buyer, &1_000_000_000);
+    client.set_coin_price(&asset, &10_000_000);
+    asset
+}
+
+#[test]
+fn test_purchase_coins() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let buyer = Address::generate(&env);
+    let asset = priced_asset(&env, &client, &buyer);
+    let xlm = TokenClient::new(&env, &asset);
+
+    client.purchase_coins(&buyer, &asset, &30);
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 2..),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Mint {
+                    to: buyer.clone(),
+                    amount: 30,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                CoinsPurchased {
+                    buyer: buyer.clone(),
+                    asset: asset.clone(),
+                    amount: 30,
+                    cost: 300_000_000,
+                    balance: 30,
+                },
+            ),
+        ]
+    );
+    assert_eq!(client.get_coins(&buyer), 30);
+    assert_eq!(xlm.balance(&buyer), 700_000_000);
+    assert_eq!(xlm.balance(&client.address), 300_000_000);
+
+    // Bought coins spend like earned ones
+    let pet_id = client.create(&buyer, &String::from_str(&env, "Rich")).id;
+    assert_eq!(
+        client.try_mint_glasses(&buyer, &pet_id),
+        Err(Ok(Error::InsufficientCoins))
+    );
+    client.purchase_coins(&buyer, &asset, &20);
+    client.mint_glasses(&buyer, &pet_id);
+    assert_eq!(client.get_coins(&buyer), 0);
+}
+
+#[test]
+fn test_purchase_coins_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let buyer = Address::generate(&env);
+    let asset = priced_asset(&env, &client, &buyer);
+
+    assert_eq!(
+        client.try_purchase_coins(&buyer, &asset, &-1),
+        Err(Ok(Error::NegativeAmount))
+    );
+    assert_eq!(
+        client.try_purchase_coins(&buyer, &asset, &i128::MAX),
+        Err(Ok(Error::BalanceOverflow))
+    );
+    // The buyer only holds 100 units
+    assert!(client.try_purchase_coins(&buyer, &asset, &101).is_err());
+    assert_eq!(client.get_coins(&buyer), 0);
+
+    let unpriced = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    assert_eq!(
+        client.try_purchase_coins(&buyer, &unpriced, &1),
+        Err(Ok(Error::AssetNotAccepted))
+    );
+
+    // Taking the price away stops sales
+    client.set_coin_price(&asset, &0);
+    assert_eq!(client.coin_price(&asset), None);
+    assert_eq!(
+        client.try_purchase_coins(&buyer, &asset, &1),
+        Err(Ok(Error::AssetNotAccepted))
+    );
+    assert_eq!(
+        client.try_set_coin_price(&asset, &-1),
+        Err(Ok(Error::InvalidConfig))
+    );
+}
+
+#[test]
+fn test_withdraw_treasury() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let buyer = Address::generate(&env);
+    let asset = priced_asset(&env, &client, &buyer);
+    let xlm = TokenClient::new(&env, &asset);
+    client.purchase_coins(&buyer, &asset, &30);
+
+    let vault = Address::generate(&env);
+    let admin = client.get_admin();
+    client.withdraw_treasury(&asset, &vault, &100_000_000);
+    let events = env.events().all();
+    assert_eq!(
+        env.auths(),
+        std::vec![(
+            admin,
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    client.address.clone(),
+                    Symbol::new(&env, "withdraw_treasury"),
+                    (asset.clone(), vault.clone(), 100_000_000_i128).into_val(&env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                TreasuryWithdrawn {
+                    asset: asset.clone(),
+                    to: vault.clone(),
+                    amount: 100_000_000,
+                },
+            ),
+        ]
+    );
+    assert_eq!(xlm.balance(&vault), 100_000_000);
+    assert_eq!(xlm.balance(&client.address), 200_000_000);
+
+    // Coins already bought stay with the player
+    assert_eq!(client.get_coins(&buyer), 30);
+    assert!(client
+        .try_withdraw_treasury(&asset, &vault, &200_000_001)
+        .is_err());
+    assert_eq!(
+        client.try_withdraw_treasury(&asset, &vault, &-1),
+        Err(Ok(Error::NegativeAmount))
+    );
+}
+
+#[test]
+fn test_treasury_is_admin_only() {
+    let env = Env::default();
+    let client = create_tamagotchi_contract(&env);
+    let intruder = Address::generate(&env);
+    let asset = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &intruder,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "set_coin_price",
+                args: (&asset, 1_i128).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_coin_price(&asset, &1);
+    assert!(result.is_err());
+    assert_eq!(client.coin_price(&asset), None);
+
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &intruder,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "withdraw_treasury",
+                args: (&asset, &intruder, 0_i128).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_withdraw_treasury(&asset, &intruder, &0);
+    assert!(result.is_err());
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                 SHOP TESTS                                 */
+/* -------------------------------------------------------------------------- */
+
+fn catalog_item(
+    env: &Env,
+    id: &str,
+    category: ItemCategory,
+    price: i128,
+    slot: CosmeticSlot,
+) -> Item {
+    Item {
+        id: Symbol::new(env, id),
+        name: String::from_str(env, id),
+        category,
+        price,
+        stock: None,
+        effects: StatEffects::default(),
+        slot,
+    }
+}
+
+// Adds a hat and a monocle to the catalog and cuts the apples down to three
+fn stock_shop(env: &Env, client: &TamagotchiContractClient) {
+    client.set_item(&catalog_item(
+        env,
+        "hat",
+        ItemCategory::Accessory,
+        30,
+        CosmeticSlot::Head,
+    ));
+    client.set_item(&catalog_item(
+        env,
+        "monocle",
+        ItemCategory::Accessory,
+        20,
+        CosmeticSlot::Face,
+    ));
+    client.set_item(&Item {
+        stock: Some(3),
+        effects: StatEffects {
+            hunger: 20,
+            ..StatEffects::default()
+        },
+        ..catalog_item(env, "apple", ItemCategory::Food, 5, CosmeticSlot::None)
+    });
+}
+
+#[test]
+fn test_shop_sells_glasses_by_default() {
+    let env = Env::default();
+    let client = create_tamagotchi_contract(&env);
+
+    let glasses = Item {
+        id: symbol_short!("glasses"),
+        name: String::from_str(&env, "Cool Glasses"),
+        category: ItemCategory::Accessory,
+        price: 50,
+        stock: None,
+        effects: StatEffects::default(),
+        slot: CosmeticSlot::Face,
+    };
+    assert_eq!(client.list_items(&0, &10), Item::starter_catalog(&env));
+    assert_eq!(client.list_items(&0, &1), vec![&env, glasses.clone()]);
+    assert_eq!(client.get_item(&symbol_short!("glasses")), glasses);
+    assert_eq!(
+        client.try_get_item(&symbol_short!("hat")),
+        Err(Ok(Error::UnknownItem))
+    );
+}
+
+#[test]
+fn test_catalog_management() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    stock_shop(&env, &client);
+
+    let ids = |items: Vec<Item>| -> std::vec::Vec<Symbol> { items.iter().map(|i| i.id).collect() };
+    assert_eq!(
+        ids(client.list_items(&0, &2)),
+        [symbol_short!("glasses"), symbol_short!("apple")]
+    );
+    assert_eq!(
+        ids(client.list_items(&5, &2)),
+        [symbol_short!("hat"), symbol_short!("monocle")]
+    );
+    assert_eq!(client.list_items(&7, &2), vec![&env]);
+    assert_eq!(client.list_items(&0, &u32::MAX).len(), 7);
+
+    // Updating an item keeps its place in the catalog
+    let hat = Item {
+        price: 45,
+        ..client.get_item(&symbol_short!("hat"))
+    };
+    client.set_item(&hat);
+    assert_eq!(client.list_items(&5, &1), vec![&env, hat]);
+    assert_eq!(client.list_items(&0, &10).len(), 7);
+
+    let floating_hat = catalog_item(&env, "cap", ItemCategory::Accessory, 10, CosmeticSlot::None);
+    let wearable_cake = catalog_item(&env, "cake", ItemCategory::Food, 10, CosmeticSlot::Head);
+    let free_money = catalog_item(&env, "ball", ItemCategory::Toy, -10, CosmeticSlot::None);
+    for item in [floating_hat, wearable_cake, free_money] {
+        assert_eq!(client.try_set_item(&item), Err(Ok(Error::InvalidItem)));
+    }
+    assert_eq!(client.list_items(&0, &10).len(), 7);
+}
+
+#[test]
+fn test_catalog_is_admin_only() {
+    let env = Env::default();
+    let client = create_tamagotchi_contract(&env);
+    let intruder = Address::generate(&env);
+    let bargain = Item {
+        price: 0,
+        ..client.get_item(&symbol_short!("glasses"))
+    };
+
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &intruder,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "set_item",
+                args: (&bargain,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_item(&bargain);
+    assert!(result.is_err());
+    assert_eq!(client.get_item(&symbol_short!("glasses")).price, 50);
+}
+
+#[test]
+fn test_buy_item() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    stock_shop(&env, &client);
+    let owner = owner_with_coins(&env, &client, 1);
+    let apple = symbol_short!("apple");
+
+    client.buy_item(&owner, &apple, &2);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                Burn {
+                    from: owner.clone(),
+                    amount: 10,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                CoinsSpent {
+                    owner: owner.clone(),
+                    amount: 10,
+                    balance: 15,
+                },
+            ),
+            contract_event(
+                &env,
+                &client,
+                ItemBought {
+                    owner: owner.clone(),
+                    item: apple.clone(),
+                    quantity: 2,
+                    cost: 10,
+                },
+            ),
+        ]
+    );
+    assert_eq!(client.get_inventory(&owner), map![&env, (apple.clone(), 2)]);
+    assert_eq!(client.get_item(&apple).stock, Some(1));
+
+    assert_eq!(
+        client.try_buy_item(&owner, &apple, &2),
+        Err(Ok(Error::OutOfStock))
+    );
+    assert_eq!(
+        client.try_buy_item(&owner, &apple, &0),
+        Err(Ok(Error::InvalidQuantity))
+    );
+    assert_eq!(
+        client.try_buy_item(&owner, &symbol_short!("hat"), &1),
+        Err(Ok(Error::InsufficientCoins))
+    );
+    assert_eq!(
+        client.try_buy_item(&owner, &symbol_short!("kite"), &1),
+        Err(Ok(Error::UnknownItem))
+    );
+
+    // Failed purchases leave stock, coins and inventory alone
+    client.buy_item(&owner, &apple, &1);
+    assert_eq!(client.get_item(&apple).stock, Some(0));
+    assert_eq!(client.get_coins(&owner), 10);
+    assert_eq!(client.get_inventory(&owner), map![&env, (apple, 3)]);
+}
+
+#[test]
+fn test_equip_and_unequip_items() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    stock_shop(&env, &client);
+    let owner = owner_with_coins(&env, &client, 4);
+    let pet_id = client.list_pets(&owner).get_unchecked(0);
+    let glasses = symbol_short!("glasses");
+    let monocle = symbol_short!("monocle");
+
+    client.buy_item(&owner, &glasses, &1);
+    client.buy_item(&owner, &monocle, &1);
+    client.equip_item(&owner, &pet_id, &glasses);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                ItemEquipped {
+                    owner: owner.clone(),
+                    pet_id,
+                    item: glasses.clone(),
+                    slot: CosmeticSlot::Face,
+                },
+            ),
+        ]
+    );
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(
+        pet.equipped,
+        map![&env, (CosmeticSlot::Face, glasses.clone())]
+    );
+    assert!(pet.has_glasses);
+    assert_eq!(
+        client.get_inventory(&owner),
+        map![&env, (monocle.clone(), 1)]
+    );
+
+    // One item per slot, the glasses go back to the inventory
+    client.equip_item(&owner, &pet_id, &monocle);
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(
+        pet.equipped,
+        map![&env, (CosmeticSlot::Face, monocle.clone())]
+    );
+    assert!(!pet.has_glasses);
+    assert_eq!(
+        client.get_inventory(&owner),
+        map![&env, (glasses.clone(), 1)]
+    );
+
+    client.unequip_item(&owner, &pet_id, &CosmeticSlot::Face);
+    assert_eq!(client.get_pet(&pet_id).equipped, map![&env]);
+    assert_eq!(
+        client.get_inventory(&owner),
+        map![&env, (glasses.clone(), 1), (monocle.clone(), 1)]
+    );
+
+    assert_eq!(
+        client.try_unequip_item(&owner, &pet_id, &CosmeticSlot::Face),
+        Err(Ok(Error::SlotEmpty))
+    );
+    assert_eq!(
+        client.try_equip_item(&owner, &pet_id, &symbol_short!("hat")),
+        Err(Ok(Error::ItemNotOwned))
+    );
+    client.buy_item(&owner, &symbol_short!("apple"), &1);
+    assert_eq!(
+        client.try_equip_item(&owner, &pet_id, &symbol_short!("apple")),
+        Err(Ok(Error::NotEquippable))
+    );
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_equip_item(&stranger, &pet_id, &glasses),
+        Err(Ok(Error::NotOwner))
+    );
+}
+
+#[test]
+fn test_mint_glasses_goes_through_the_shop() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = owner_with_coins(&env, &client, 4);
+    let pet_id = client.list_pets(&owner).get_unchecked(0);
+    client.set_item(&Item {
+        price: 40,
+        stock: Some(1),
+        ..client.get_item(&symbol_short!("glasses"))
+    });
+
+    client.mint_glasses(&owner, &pet_id);
+    let pet = client.get_pet(&pet_id);
+    assert!(pet.has_glasses);
+    assert_eq!(
+        pet.equipped,
+        map![&env, (CosmeticSlot::Face, symbol_short!("glasses"))]
+    );
+    assert_eq!(client.get_coins(&owner), 60);
+    assert_eq!(
+        client.try_mint_glasses(&owner, &pet_id),
+        Err(Ok(Error::OutOfStock))
+    );
+}
+
+#[test]
+fn test_gifted_consumables_go_to_the_inventory() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    stock_shop(&env, &client);
+    let giver = owner_with_coins(&env, &client, 1);
+    let friend = Address::generate(&env);
+    let pet_id = client.create(&friend, &String::from_str(&env, "Snacky")).id;
+
+    client.gift_item(&giver, &pet_id, &symbol_short!("apple"));
+    client.gift_item(&giver, &pet_id, &symbol_short!("apple"));
+    assert_eq!(
+        client.get_inventory(&friend),
+        map![&env, (symbol_short!("apple"), 2)]
+    );
+    assert_eq!(client.get_pet(&pet_id).equipped, map![&env]);
+    assert_eq!(client.get_coins(&giver), 15);
+}
+
+/* -------------------------------------------------------------------------- */
+/*                              CONSUMABLE TESTS                              */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+fn test_feed_and_play_with_items() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = owner_with_coins(&env, &client, 1);
+    let pet_id = client.list_pets(&owner).get_unchecked(0);
+    let cake = symbol_short!("cake");
+    let ball = symbol_short!("ball");
+    client.buy_item(&owner, &cake, &1);
+    client.buy_item(&owner, &ball, &1);
+    advance_ledger(&env, 3600 * 10);
+
+    let before = client.get_pet(&pet_id);
+    client.feed(&owner, &pet_id, &Some(cake.clone()));
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                ItemUsed {
+                    owner: owner.clone(),
+                    pet_id,
+                    item: cake,
+                    action: symbol_short!("feed"),
+                },
+            ),
+        ]
+    );
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.hunger, (before.hunger + 35).min(MAX_STAT));
+    assert_eq!(pet.happiness, (before.happiness + 15).min(MAX_STAT));
+    assert_eq!(pet.health, before.health - 5);
+    assert_eq!(client.get_inventory(&owner), map![&env, (ball.clone(), 1)]);
+
+    let before = pet;
+    client.play(&owner, &pet_id, &Some(ball));
+    let pet = client.get_pet(&pet_id);
+    assert_eq!(pet.happiness, (before.happiness + 25).min(MAX_STAT));
+    assert_eq!(pet.energy, before.energy - 10);
+    assert_eq!(client.get_inventory(&owner), map![&env]);
+    assert_eq!(client.get_coins(&owner), 0);
+}
+
+#[test]
+fn test_use_item_medicine_cures_sickness() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = owner_with_coins(&env, &client, 1);
+    let pet_id = client.list_pets(&owner).get_unchecked(0);
+    let medicine = symbol_short!("medicine");
+    client.buy_item(&owner, &medicine, &1);
+    overfeed_until_sick(&env, &client, &owner, pet_id);
+    advance_ledger(&env, 3600 * 20);
+    assert_eq!(client.get_pet(&pet_id).health, 60);
+
+    client.use_item(&owner, &pet_id, &medicine);
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &env,
+            contract_event(
+                &env,
+                &client,
+                ItemUsed {
+                    owner: owner.clone(),
+                    pet_id,
+                    item: medicine.clone(),
+                    action: symbol_short!("medicine"),
+                },
+            ),
+        ]
+    );
+    let pet = client.get_pet(&pet_id);
+    assert!(!pet.is_sick);
+    assert_eq!(pet.health, 100); // 60 + 40
+    assert_eq!(client.get_inventory(&owner), map![&env]);
+    assert_eq!(
+        client.try_use_item(&owner, &pet_id, &medicine),
+        Err(Ok(Error::ItemNotOwned))
+    );
+}
+
+#[test]
+fn test_use_item_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_tamagotchi_contract(&env);
+    let owner = owner_with_coins(&env, &client, 3);
+    let pet_id = client.list_pets(&owner).get_unchecked(0);
+    let apple = symbol_short!("apple");
+    let ball = symbol_short!("ball");
+    let glasses = symbol_short!("glasses");
+    client.buy_item(&owner, &apple, &1);
+    client.buy_item(&owner, &ball, &1);
+    client.buy_item(&owner, &glasses, &1);
+    advance_ledger(&env, 3600 * 10);
+
+    assert_eq!(
+        client.try_feed(&owner, &pet_id, &Some(ball.clone())),
+        Err(Ok(Error::UnsuitableItem))
+    );
+    assert_eq!(
+        client.try_play(&owner, &pet_id, &Some(apple.clone())),
+        Err(Ok(Error::UnsuitableItem))
+    );
+    assert_eq!(
+        client.try_use_item(&owner, &pet_id, &glasses),
+        Err(Ok(Error::UnsuitableItem))
+    );
+    assert_eq!(
+        client.try_use_item(&owner, &pet_id, &symbol_short!("kite")),
+        Err(Ok(Error::UnknownItem))
+    );
+    assert_eq!(
+        client.try_feed(&owner, &pet_id, &Some(symbol_short!("cake"))),
+        Err(Ok(Error::ItemNotOwned))
+    );
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_use_item(&stranger, &pet_id, &apple),
+        Err(Ok(Error::NotOwner))
+    );
+
+    // An action that fails keeps the item
+    client.feed(&owner, &pet_id, &None);
+    assert_eq!(
+        client.try_use_item(&owner, &pet_id, &apple),
+        Err(Ok(Error::ActionOnCooldown))
+    );
+    assert_eq!(
+        client.get_inventory(&owner),
+        map![&env, (apple, 1), (ball, 1), (glasses, 1)]
+    );
 }
@@ -0,0 +1,182 @@
+//! Coins as a SEP-41 token.
+//!
+//! Balances live under `DataKey::Coins`, so coins earned before the contract
+//! became a token carry over. Coins are whole numbers: work mints them and
+//! the shop burns them, there is no other way in or out of circulation.
+use soroban_sdk::{
+    contractimpl, panic_with_error, token::TokenInterface, Address, Env, MuxedAddress, String,
+};
+
+use crate::events::{Approve, Burn, Mint, Transfer, TransferMuxed};
+use crate::storage::{self, AllowanceValue};
+use crate::{Error, TamagotchiContract, TamagotchiContractArgs, TamagotchiContractClient};
+
+const NAME: &str = "Tamago Coin";
+const SYMBOL: &str = "TAMA";
+const DECIMALS: u32 = 0;
+
+#[contractimpl]
+impl TokenInterface for TamagotchiContract {
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        storage::extend_instance(&env);
+        storage::read_allowance(&env, &from, &spender).amount
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+        storage::extend_instance(&env);
+        check_amount(&env, amount);
+        if amount > 0 && expiration_ledger < env.ledger().sequence() {
+            panic_with_error!(&env, Error::InvalidExpirationLedger);
+        }
+
+        let allowance = AllowanceValue {
+            amount,
+            expiration_ledger,
+        };
+        storage::write_allowance(&env, &from, &spender, &allowance);
+
+        Approve {
+            from,
+            spender,
+            amount,
+            expiration_ledger,
+        }
+        .publish(&env);
+    }
+
+    fn balance(env: Env, id: Address) -> i128 {
+        storage::extend_instance(&env);
+        storage::read_coins(&env, &id)
+    }
+
+    fn transfer(env: Env, from: Address, to: MuxedAddress, amount: i128) {
+        from.require_auth();
+        storage::extend_instance(&env);
+        check_amount(&env, amount);
+        let to_address = to.address();
+        move_coins(&env, &from, &to_address, amount).unwrap_or_else(|e| panic_with_error!(&env, e));
+
+        match to.id() {
+            Some(to_muxed_id) => TransferMuxed {
+                from,
+                to: to_address,
+                to_muxed_id,
+                amount,
+            }
+            .publish(&env),
+            None => Transfer {
+                from,
+                to: to_address,
+                amount,
+            }
+            .publish(&env),
+        }
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        storage::extend_instance(&env);
+        check_amount(&env, amount);
+        spend_allowance(&env, &from, &spender, amount);
+        move_coins(&env, &from, &to, amount).unwrap_or_else(|e| panic_with_error!(&env, e));
+
+        Transfer { from, to, amount }.publish(&env);
+    }
+
+    fn burn(env: Env, from: Address, amount: i128) {
+        from.require_auth();
+        storage::extend_instance(&env);
+        check_amount(&env, amount);
+        burn(&env, &from, amount).unwrap_or_else(|e| panic_with_error!(&env, e));
+    }
+
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+        storage::extend_instance(&env);
+        check_amount(&env, amount);
+        spend_allowance(&env, &from, &spender, amount);
+        burn(&env, &from, amount).unwrap_or_else(|e| panic_with_error!(&env, e));
+    }
+
+    fn decimals(_env: Env) -> u32 {
+        DECIMALS
+    }
+
+    fn name(env: Env) -> String {
+        String::from_str(&env, NAME)
+    }
+
+    fn symbol(env: Env) -> String {
+        String::from_str(&env, SYMBOL)
+    }
+}
+
+// Create coins out of thin air, returns the new balance
+pub(crate) fn mint(env: &Env, to: &Address, amount: i128) -> Result<i128, Error> {
+    let balance = storage::read_coins(env, to)
+        .checked_add(amount)
+        .ok_or(Error::BalanceOverflow)?;
+    storage::write_coins(env, to, balance);
+
+    Mint {
+        to: to.clone(),
+        amount,
+    }
+    .publish(env);
+    Ok(balance)
+}
+
+// Destroy coins, returns the new balance
+pub(crate) fn burn(env: &Env, from: &Address, amount: i128) -> Result<i128, Error> {
+    let balance = storage::read_coins(env, from);
+    if balance < amount {
+        return Err(Error::InsufficientCoins);
+    }
+    let balance = balance - amount;
+    storage::write_coins(env, from, balance);
+
+    Burn {
+        from: from.clone(),
+        amount,
+    }
+    .publish(env);
+    Ok(balance)
+}
+
+fn check_amount(env: &Env, amount: i128) {
+    if amount < 0 {
+        panic_with_error!(env, Error::NegativeAmount);
+    }
+}
+
+// Move coins between balances without publishing anything, callers report
+// the transfer the way their interface expects
+pub(crate) fn move_coins(
+    env: &Env,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+) -> Result<(), Error> {
+    let balance = storage::read_coins(env, from);
+    if balance < amount {
+        return Err(Error::InsufficientCoins);
+    }
+    storage::write_coins(env, from, balance - amount);
+    let balance = storage::read_coins(env, to)
+        .checked_add(amount)
+        .ok_or(Error::BalanceOverflow)?;
+    storage::write_coins(env, to, balance);
+    Ok(())
+}
+
+fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+    let mut allowance = storage::read_allowance(env, from, spender);
+    if allowance.amount < amount {
+        panic_with_error!(env, Error::InsufficientAllowance);
+    }
+    if amount > 0 {
+        allowance.amount -= amount;
+        storage::write_allowance(env, from, spender, &allowance);
+    }
+}